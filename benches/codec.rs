@@ -0,0 +1,123 @@
+//! Decode, encode and TXID benchmarks over a generated dump.
+//!
+//! ```bash
+//! cargo bench --bench codec
+//! cargo bench --bench codec --features parallel
+//! ```
+//!
+//! The dump is generated from a fixed seed rather than checked in, so every
+//! run measures the same bytes.
+
+use bitcoin::{
+    Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+    absolute::LockTime,
+    hashes::{Hash, sha256d},
+    transaction::Version,
+};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use windfish::{MEMPOOL_DUMP_VERSION, MempoolSerde, Txn};
+
+/// Transactions in the generated dump, about 40 MB encoded.
+const TX_COUNT: u32 = 100_000;
+
+/// Returns `len` pseudo-random bytes derived from `seed` and `tag`.
+fn filler(seed: u32, tag: u8, len: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len);
+    let mut block = 0u32;
+    while bytes.len() < len {
+        let mut preimage = seed.to_le_bytes().to_vec();
+        preimage.push(tag);
+        preimage.extend(block.to_le_bytes());
+        bytes.extend(sha256d::Hash::hash(&preimage).to_byte_array());
+        block += 1;
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+/// Builds the `i`th transaction: one to three P2WPKH-shaped inputs and two
+/// outputs, like the bulk of a real mempool.
+fn generate_tx(i: u32) -> Transaction {
+    let input = (0..=i % 3)
+        .map(|vin| TxIn {
+            previous_output: OutPoint::new(Txid::from_slice(&filler(i, 0, 32)).unwrap(), vin),
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::from_slice(&[
+                filler(i, 1 + u8::try_from(vin).unwrap(), 72),
+                filler(i, 4, 33),
+            ]),
+        })
+        .collect();
+    let output = (0..2u8)
+        .map(|vout| TxOut {
+            value: Amount::from_sat(u64::from(i) * 1_000 + u64::from(vout)),
+            script_pubkey: ScriptBuf::from_bytes(
+                [&[0x00, 0x14][..], &filler(i, 8 + vout, 20)].concat(),
+            ),
+        })
+        .collect();
+
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input,
+        output,
+    }
+}
+
+/// Generates a V2 dump of [`TX_COUNT`] transactions, a fee delta on every
+/// hundredth and a few unbroadcast TXIDs.
+fn generate_dump() -> MempoolSerde {
+    let mut mempool = MempoolSerde::empty(MEMPOOL_DUMP_VERSION);
+    mempool.xor_key = Some(filler(0, 0xff, 8).try_into().unwrap());
+    for i in 0..TX_COUNT {
+        let fee_delta = if i % 100 == 0 { i64::from(i) } else { 0 };
+        let txn = Txn::new(generate_tx(i), 1_700_000_000 + i64::from(i), fee_delta);
+        if fee_delta != 0 {
+            mempool.map_deltas.insert(txn.txid(), fee_delta);
+        }
+        if i % 1_000 == 0 {
+            mempool.unbroadcast_txids.insert(txn.txid());
+        }
+        mempool.txs.push(txn);
+    }
+    mempool
+}
+
+fn codec(c: &mut Criterion) {
+    let mempool = generate_dump();
+    let bytes = mempool.to_bytes().unwrap();
+
+    let mut group = c.benchmark_group("codec");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_function("from_bytes", |b| {
+        b.iter(|| MempoolSerde::from_bytes(black_box(&bytes)).unwrap());
+    });
+    #[cfg(feature = "parallel")]
+    group.bench_function("from_bytes_parallel", |b| {
+        b.iter(|| MempoolSerde::from_bytes_parallel(black_box(&bytes)).unwrap());
+    });
+    group.bench_function("to_bytes", |b| {
+        b.iter(|| black_box(&mempool).to_bytes().unwrap());
+    });
+    group.finish();
+}
+
+fn txid(c: &mut Criterion) {
+    let txn = Txn::new(generate_tx(0), 0, 0);
+    let mut group = c.benchmark_group("txid");
+    group.bench_function("compute_txid", |b| {
+        b.iter(|| black_box(&txn.tx).compute_txid());
+    });
+    assert_eq!(txn.txid(), txn.tx.compute_txid());
+    group.bench_function("memoized_txid", |b| {
+        b.iter(|| black_box(&txn).txid());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, codec, txid);
+criterion_main!(benches);
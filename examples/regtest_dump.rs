@@ -0,0 +1,49 @@
+//! Writes a V1 mempool.dat holding the given raw transactions, for priming a
+//! regtest node's mempool.
+//!
+//! ```bash
+//! cargo run --example regtest_dump -- mempool.dat <raw-tx-hex>...
+//! cargo run --example regtest_dump -- mempool.dat < txs.txt
+//! ```
+//!
+//! With no hex arguments, one transaction is read per line of stdin. Entries
+//! are timestamped one second apart, ending now, so Bitcoin Core neither
+//! expires them on load nor reorders them. Fee deltas are zero.
+
+use bitcoin::{Transaction, consensus::encode::deserialize_hex};
+use std::{io::BufRead, path::PathBuf};
+use windfish::{MEMPOOL_DUMP_VERSION_NO_XOR_KEY, MempoolSerde};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let output: PathBuf = args
+        .next()
+        .ok_or("usage: regtest_dump <output> [raw-tx-hex...]")?
+        .into();
+
+    let mut hexes: Vec<String> = args.collect();
+    if hexes.is_empty() {
+        for line in std::io::stdin().lock().lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                hexes.push(line.trim().to_string());
+            }
+        }
+    }
+
+    let start = chrono::Utc::now().timestamp() - i64::try_from(hexes.len())?;
+    let mut mempool = MempoolSerde::empty(MEMPOOL_DUMP_VERSION_NO_XOR_KEY);
+    for (time, hex) in (start + 1..).zip(&hexes) {
+        let tx: Transaction =
+            deserialize_hex(hex).map_err(|e| format!("invalid transaction {hex}: {e}"))?;
+        mempool.add_transaction(tx, time, 0)?;
+    }
+
+    mempool.write_to_file(&output)?;
+    println!(
+        "Wrote {} transaction(s) to {}",
+        mempool.txs.len(),
+        output.display()
+    );
+    Ok(())
+}
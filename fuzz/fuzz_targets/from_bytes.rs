@@ -0,0 +1,11 @@
+//! Feeds arbitrary bytes to `MempoolSerde::from_bytes`, which must reject
+//! malformed dumps with an error rather than panic or exhaust memory.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use windfish::MempoolSerde;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = MempoolSerde::from_bytes(data);
+});
@@ -0,0 +1,124 @@
+//! Gzip and zstd compressed dumps, recognized by their file extension.
+
+use crate::MempoolResult;
+use std::{fs::File, io::Read, path::Path};
+
+/// Compression wrapped around the mempool format: `.gz` files are gzip and
+/// `.zst` files are zstd, anything else is a plain dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Self::Gzip,
+            Some("zst") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+
+    /// Opens `path` for reading its decompressed contents.
+    pub fn open(path: &Path) -> MempoolResult<Box<dyn Read>> {
+        Self::from_path(path).decoder(File::open(path)?)
+    }
+
+    /// Wraps `file` in a decoder, or returns it as is if uncompressed.
+    pub fn decoder(self, file: File) -> MempoolResult<Box<dyn Read>> {
+        match self {
+            Self::None => Ok(Box::new(file)),
+            #[cfg(feature = "gzip")]
+            Self::Gzip => Ok(Box::new(flate2::read::GzDecoder::new(file))),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Ok(Box::new(zstd::Decoder::new(file)?)),
+            #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+            _ => Err(self.disabled()),
+        }
+    }
+
+    /// Compresses an encoded dump for writing.
+    pub fn compress(self, bytes: Vec<u8>) -> MempoolResult<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes),
+            #[cfg(feature = "gzip")]
+            Self::Gzip => {
+                use std::io::Write;
+
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&bytes)?;
+                Ok(encoder.finish()?)
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Ok(zstd::encode_all(bytes.as_slice(), 0)?),
+            #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+            _ => Err(self.disabled()),
+        }
+    }
+
+    #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+    const fn disabled(self) -> crate::MempoolSerdeError {
+        crate::MempoolSerdeError::CompressionDisabled(match self {
+            Self::Gzip => "gzip",
+            _ => "zstd",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MempoolSerde, MempoolSerdeError};
+
+    #[test]
+    fn compression_follows_extension() {
+        assert_eq!(
+            Compression::from_path(Path::new("mempool.dat.gz")),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("mempool.dat.zst")),
+            Compression::Zstd
+        );
+        assert_eq!(
+            Compression::from_path(Path::new("mempool.dat")),
+            Compression::None
+        );
+    }
+
+    #[test]
+    fn compressed_dumps_roundtrip() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let dir = std::env::temp_dir().join(format!("windfish-compress-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for (name, enabled) in [
+            ("mempool.dat.gz", cfg!(feature = "gzip")),
+            ("mempool.dat.zst", cfg!(feature = "zstd")),
+        ] {
+            let path = dir.join(name);
+            let written = mempool.write_to_file(&path);
+            if !enabled {
+                assert!(matches!(
+                    written,
+                    Err(MempoolSerdeError::CompressionDisabled(_))
+                ));
+                continue;
+            }
+            written.unwrap();
+
+            // The file on disk must not be a plain dump.
+            assert_ne!(std::fs::read(&path).unwrap(), mempool.to_bytes().unwrap());
+            assert_eq!(MempoolSerde::new(&path).unwrap(), mempool);
+            assert_eq!(
+                MempoolSerde::peek_count(&path).unwrap(),
+                mempool.txs.len() as u64
+            );
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+//! CSV export of transaction metadata.
+
+use crate::{MempoolResult, MempoolSerde};
+use bitcoin::Amount;
+use std::io::Write;
+
+impl MempoolSerde {
+    /// Writes one CSV row per transaction, in file order, preceded by a
+    /// header row.
+    ///
+    /// Columns are `txid` (quoted), `time`, `fee_delta`, `vsize`,
+    /// `num_inputs`, `num_outputs` and `total_output_value` (in sats, left
+    /// empty if it overflows).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `w` fails.
+    pub fn write_csv<W: Write>(&self, mut w: W) -> MempoolResult<()> {
+        writeln!(
+            w,
+            "txid,time,fee_delta,vsize,num_inputs,num_outputs,total_output_value"
+        )?;
+
+        for (txid, txn) in self.iter_with_txid() {
+            let total_output_value = txn
+                .tx
+                .output
+                .iter()
+                .map(|out| out.value)
+                .try_fold(Amount::ZERO, Amount::checked_add)
+                .map(|total| total.to_sat().to_string())
+                .unwrap_or_default();
+            writeln!(
+                w,
+                "\"{txid}\",{},{},{},{},{},{total_output_value}",
+                txn.time,
+                txn.fee_delta,
+                txn.vsize(),
+                txn.tx.input.len(),
+                txn.tx.output.len(),
+            )?;
+        }
+
+        w.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn write_csv_emits_header_and_rows() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let mut out = Vec::new();
+        mempool.write_csv(&mut out).unwrap();
+
+        let csv = String::from_utf8(out).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("txid,time,fee_delta,vsize,num_inputs,num_outputs,total_output_value")
+        );
+
+        let txn = &mempool.txs[0];
+        let first = lines.next().unwrap();
+        assert!(first.starts_with(&format!("\"{}\",{},", txn.tx.compute_txid(), txn.time)));
+        assert_eq!(lines.count(), mempool.txs.len() - 1);
+    }
+
+    #[test]
+    fn write_csv_leaves_overflowing_totals_empty() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        mempool.txs.truncate(1);
+        let tx = mempool.txs[0].tx_mut();
+        tx.output.truncate(1);
+        tx.output[0].value = Amount::MAX;
+        tx.output.push(tx.output[0].clone());
+
+        let mut out = Vec::new();
+        mempool.write_csv(&mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv.lines().nth(1).unwrap().ends_with(",2,"));
+    }
+}
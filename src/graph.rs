@@ -0,0 +1,167 @@
+//! Parent/child relationships between transactions in a dump.
+
+use crate::MempoolSerde;
+use bitcoin::{OutPoint, Transaction, Txid};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+impl MempoolSerde {
+    /// Returns the transactions in the dump that `txid` spends from, directly
+    /// or through other in-dump parents, nearest first.
+    ///
+    /// Parents that are not in the dump (confirmed or missing) are not listed.
+    #[must_use]
+    pub fn ancestors(&self, txid: &Txid) -> Vec<Txid> {
+        let graph = Graph::new(self);
+        walk(txid, |txid| graph.parents(txid))
+    }
+
+    /// Returns the transactions in the dump that spend outputs of `txid`,
+    /// directly or through other in-dump children, nearest first.
+    #[must_use]
+    pub fn descendants(&self, txid: &Txid) -> Vec<Txid> {
+        let graph = Graph::new(self);
+        walk(txid, |txid| graph.children(txid))
+    }
+
+    /// Returns the transactions that spend at least one output of a
+    /// transaction not in the dump, in file order.
+    ///
+    /// This is a heuristic, not a validity judgment: spending confirmed
+    /// outputs is normal, and a dump does not say which parents are
+    /// confirmed. It narrows down entries Bitcoin Core may reject as orphans
+    /// on load, e.g. after their in-mempool parent was removed.
+    #[must_use]
+    pub fn orphans(&self) -> Vec<Txid> {
+        let graph = Graph::new(self);
+        self.iter_with_txid()
+            .filter(|(_, txn)| {
+                txn.tx
+                    .input
+                    .iter()
+                    .any(|txin| !graph.txs.contains_key(&txin.previous_output.txid))
+            })
+            .map(|(txid, _)| txid)
+            .collect()
+    }
+}
+
+/// Indexes built once per query so each step of a walk is a map lookup
+/// rather than a scan of `txs`.
+struct Graph<'a> {
+    txs: HashMap<Txid, &'a Transaction>,
+    /// Which transactions in the dump spend each outpoint; an edited dump
+    /// can hold several conflicting spends of one
+    spenders: HashMap<OutPoint, Vec<Txid>>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(mempool: &'a MempoolSerde) -> Self {
+        let mut txs = HashMap::with_capacity(mempool.txs.len());
+        let mut spenders = HashMap::new();
+        for (txid, txn) in mempool.iter_with_txid() {
+            txs.insert(txid, &txn.tx);
+            for txin in &txn.tx.input {
+                spenders
+                    .entry(txin.previous_output)
+                    .or_insert_with(Vec::new)
+                    .push(txid);
+            }
+        }
+        Self { txs, spenders }
+    }
+
+    fn parents(&self, txid: &Txid) -> Vec<Txid> {
+        self.txs.get(txid).map_or_else(Vec::new, |tx| {
+            tx.input
+                .iter()
+                .map(|txin| txin.previous_output.txid)
+                .filter(|parent| self.txs.contains_key(parent))
+                .collect()
+        })
+    }
+
+    fn children(&self, txid: &Txid) -> Vec<Txid> {
+        self.txs.get(txid).map_or_else(Vec::new, |tx| {
+            (0..tx.output.len())
+                .filter_map(|vout| {
+                    let vout = u32::try_from(vout).ok()?;
+                    self.spenders.get(&OutPoint::new(*txid, vout))
+                })
+                .flatten()
+                .copied()
+                .collect()
+        })
+    }
+}
+
+/// Breadth-first walk from `start` following `next`, excluding `start`.
+fn walk(start: &Txid, next: impl Fn(&Txid) -> Vec<Txid>) -> Vec<Txid> {
+    let mut seen = HashSet::from([*start]);
+    let mut queue = VecDeque::from([*start]);
+    let mut found = Vec::new();
+    while let Some(txid) = queue.pop_front() {
+        for related in next(&txid) {
+            if seen.insert(related) {
+                found.push(related);
+                queue.push_back(related);
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn walks_spends_in_both_directions() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let parent = mempool.txs[0].tx.compute_txid();
+
+        // Rewire two transactions into a parent -> child -> grandchild chain.
+        mempool.txs[1].tx_mut().input[0].previous_output = OutPoint::new(parent, 0);
+        let child = mempool.txs[1].tx.compute_txid();
+        mempool.txs[2].tx_mut().input[0].previous_output = OutPoint::new(child, 0);
+        let grandchild = mempool.txs[2].tx.compute_txid();
+
+        assert_eq!(mempool.descendants(&parent), vec![child, grandchild]);
+        assert_eq!(mempool.ancestors(&grandchild), vec![child, parent]);
+        assert!(mempool.ancestors(&parent).is_empty());
+        assert!(mempool.descendants(&grandchild).is_empty());
+    }
+
+    #[test]
+    fn descendants_include_every_spender_of_an_outpoint() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let parent = mempool.txs[0].tx.compute_txid();
+
+        // Two conflicting children spend the same output of the parent.
+        mempool.txs[1].tx_mut().input[0].previous_output = OutPoint::new(parent, 0);
+        let first = mempool.txs[1].tx.compute_txid();
+        mempool.txs[2].tx_mut().input[0].previous_output = OutPoint::new(parent, 0);
+        let second = mempool.txs[2].tx.compute_txid();
+
+        assert_eq!(mempool.descendants(&parent), vec![first, second]);
+        assert_eq!(mempool.ancestors(&second), vec![parent]);
+    }
+
+    #[test]
+    fn orphans_spend_outside_the_dump() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let parent = mempool.txs[0].tx.compute_txid();
+
+        // A transaction whose only parent is in the dump is not an orphan.
+        mempool.txs[1].tx_mut().input.truncate(1);
+        mempool.txs[1].tx_mut().input[0].previous_output = OutPoint::new(parent, 0);
+        let child = mempool.txs[1].tx.compute_txid();
+
+        let orphans = mempool.orphans();
+        assert!(!orphans.contains(&child));
+        assert!(orphans.contains(&parent));
+
+        mempool.remove_transaction(&parent);
+        assert!(mempool.orphans().contains(&child));
+    }
+}
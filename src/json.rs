@@ -0,0 +1,192 @@
+//! JSON export and import of mempool dumps.
+
+use crate::{
+    MEMPOOL_DUMP_VERSION, MEMPOOL_DUMP_VERSION_NO_XOR_KEY, MempoolResult, MempoolSerde,
+    MempoolSerdeError, Txn, XOR_KEY_SIZE,
+};
+use bitcoin::{
+    Transaction, Txid,
+    consensus::encode::{deserialize_hex, serialize_hex},
+    hex::{DisplayHex, FromHex},
+};
+use serde_json::{Map, Value, json};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+impl MempoolSerde {
+    /// Converts the mempool into a JSON value.
+    ///
+    /// Transactions are emitted in file order with their TXID, raw hex, unix
+    /// `time` and `fee_delta`. Fee deltas are keyed by TXID and unbroadcast
+    /// TXIDs are sorted, so the output is stable across runs.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let txs: Vec<Value> = self
+            .txs
+            .iter()
+            .map(|txn| {
+                json!({
+                    "txid": txn.txid().to_string(),
+                    "hex": serialize_hex(&txn.tx),
+                    "time": txn.time,
+                    "fee_delta": txn.fee_delta,
+                })
+            })
+            .collect();
+
+        let map_deltas: Map<String, Value> = self
+            .map_deltas
+            .iter()
+            .map(|(txid, delta)| (txid.to_string(), json!(delta)))
+            .collect();
+
+        let mut unbroadcast_txids: Vec<String> = self
+            .unbroadcast_txids
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        unbroadcast_txids.sort();
+
+        json!({
+            "version": self.version,
+            "xor_key": self.xor_key.map(|key| key.to_lower_hex_string()),
+            "txs": txs,
+            "map_deltas": map_deltas,
+            "unbroadcast_txids": unbroadcast_txids,
+        })
+    }
+
+    /// Reconstructs a mempool from the JSON produced by [`Self::to_json`].
+    ///
+    /// Transactions are rebuilt from their `hex` field; the informational
+    /// `txid` field is ignored so edited transactions need not update it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MempoolSerdeError::Json`] if a field is missing or malformed,
+    /// or [`MempoolSerdeError::UnsupportedVersion`] for an unknown version.
+    pub fn from_json(value: &Value) -> MempoolResult<Self> {
+        let version = value["version"]
+            .as_u64()
+            .ok_or_else(|| json_error("missing or invalid \"version\""))?;
+        if version != MEMPOOL_DUMP_VERSION_NO_XOR_KEY && version != MEMPOOL_DUMP_VERSION {
+            return Err(MempoolSerdeError::UnsupportedVersion(version));
+        }
+
+        let xor_key = match &value["xor_key"] {
+            Value::Null => None,
+            Value::String(hex) => Some(
+                <[u8; XOR_KEY_SIZE]>::from_hex(hex)
+                    .map_err(|e| json_error(&format!("invalid \"xor_key\": {e}")))?,
+            ),
+            _ => return Err(json_error("\"xor_key\" must be a hex string or null")),
+        };
+
+        let txs = value["txs"]
+            .as_array()
+            .ok_or_else(|| json_error("missing or invalid \"txs\""))?
+            .iter()
+            .enumerate()
+            .map(|(i, tx)| {
+                let hex = tx["hex"]
+                    .as_str()
+                    .ok_or_else(|| json_error(&format!("txs[{i}]: missing \"hex\"")))?;
+                let tx_decoded = deserialize_hex::<Transaction>(hex)
+                    .map_err(|e| json_error(&format!("txs[{i}]: invalid transaction: {e}")))?;
+                let time = tx["time"]
+                    .as_i64()
+                    .ok_or_else(|| json_error(&format!("txs[{i}]: missing \"time\"")))?;
+                let fee_delta = tx["fee_delta"]
+                    .as_i64()
+                    .ok_or_else(|| json_error(&format!("txs[{i}]: missing \"fee_delta\"")))?;
+
+                Ok(Txn::new(tx_decoded, time, fee_delta))
+            })
+            .collect::<MempoolResult<Vec<Txn>>>()?;
+
+        let map_deltas = value["map_deltas"]
+            .as_object()
+            .ok_or_else(|| json_error("missing or invalid \"map_deltas\""))?
+            .iter()
+            .map(|(txid, delta)| {
+                let delta = delta
+                    .as_i64()
+                    .ok_or_else(|| json_error(&format!("map_deltas[{txid}]: invalid delta")))?;
+                Ok((parse_txid(txid)?, delta))
+            })
+            .collect::<MempoolResult<HashMap<Txid, i64>>>()?;
+
+        let unbroadcast_txids = value["unbroadcast_txids"]
+            .as_array()
+            .ok_or_else(|| json_error("missing or invalid \"unbroadcast_txids\""))?
+            .iter()
+            .map(|txid| {
+                txid.as_str()
+                    .ok_or_else(|| json_error("unbroadcast_txids: expected a string"))
+                    .and_then(parse_txid)
+            })
+            .collect::<MempoolResult<HashSet<Txid>>>()?;
+
+        Ok(Self {
+            xor_key,
+            txs,
+            map_deltas,
+            unbroadcast_txids,
+            ..Self::empty(version)
+        })
+    }
+}
+
+fn json_error(msg: &str) -> MempoolSerdeError {
+    MempoolSerdeError::Json(msg.to_string())
+}
+
+fn parse_txid(txid: &str) -> MempoolResult<Txid> {
+    Txid::from_str(txid).map_err(|e| json_error(&format!("invalid txid {txid}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn to_json_lists_every_transaction() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let value = mempool.to_json();
+
+        assert_eq!(value["version"], 2);
+        assert_eq!(value["xor_key"], "5a1c9e03b7f2446d");
+
+        let txs = value["txs"].as_array().unwrap();
+        assert_eq!(txs.len(), mempool.txs.len());
+        for (json, txn) in txs.iter().zip(&mempool.txs) {
+            assert_eq!(json["txid"], txn.tx.compute_txid().to_string());
+            assert_eq!(json["time"], txn.time);
+            assert_eq!(json["fee_delta"], txn.fee_delta);
+        }
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let bytes = std::fs::read("./test/mempool_t4_v2_001.dat").unwrap();
+        let mempool = MempoolSerde::from_bytes(&bytes).unwrap();
+
+        let rebuilt = MempoolSerde::from_json(&mempool.to_json()).unwrap();
+        assert_eq!(rebuilt.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn from_json_rejects_bad_hex() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let mut value = mempool.to_json();
+        value["txs"][0]["hex"] = json!("zz");
+
+        assert!(matches!(
+            MempoolSerde::from_json(&value),
+            Err(MempoolSerdeError::Json(_))
+        ));
+    }
+}
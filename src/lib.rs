@@ -1,172 +1,2875 @@
 /// A Bitcoin Core mempool.dat editor
 //
 use bitcoin::{
-    self, Transaction, Txid, VarInt,
+    self, Amount, FeeRate, OutPoint, ScriptBuf, Transaction, Txid, VarInt, Weight,
     consensus::{Decodable, Encodable, ReadExt, WriteExt},
 };
 use std::{
-    collections::{HashMap, HashSet},
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, HashMap, HashSet},
     fs::File,
-    io::{BufReader, Write},
-    path::Path,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    sync::OnceLock,
 };
 
+mod compress;
+mod csv;
+mod graph;
+mod json;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "parallel")]
+mod parallel;
+
+use compress::Compression;
+
 pub const MEMPOOL_DUMP_VERSION_NO_XOR_KEY: u64 = 1;
 pub const MEMPOOL_DUMP_VERSION: u64 = 2;
 
+/// Length of the obfuscation key Bitcoin Core writes into V2 dumps.
+pub const XOR_KEY_SIZE: usize = 8;
+
+/// Default ceiling on the transaction count a dump may declare before it is
+/// rejected as corrupt.
+pub const DEFAULT_MAX_TX_COUNT: u64 = 10_000_000;
+
+/// Bitcoin Core's default `-mempoolexpiry` of 336 hours, in seconds.
+pub const DEFAULT_MEMPOOL_EXPIRY_SECS: i64 = 336 * 60 * 60;
+
+/// Smallest possible encoded entry: a transaction with no inputs or outputs
+/// (version, two empty counts, lock time) followed by its time and fee delta.
+const MIN_TXN_SIZE: u64 = 4 + 1 + 1 + 4 + 8 + 8;
+
 pub type MempoolResult<T> = Result<T, MempoolSerdeError>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Txn {
+    /// The transaction. Mutate it through [`Txn::tx_mut`] once [`Txn::txid`]
+    /// may have been called, or the memoized TXID goes stale.
     pub tx: bitcoin::Transaction,
     pub time: i64,
     pub fee_delta: i64,
+    /// Memoized by [`Txn::txid`]; `OnceLock` rather than `OnceCell` keeps
+    /// `Txn` `Sync`.
+    txid: OnceLock<Txid>,
+}
+
+impl PartialEq for Txn {
+    /// Compares the entries, ignoring whether their TXIDs are memoized.
+    fn eq(&self, other: &Self) -> bool {
+        self.tx == other.tx && self.time == other.time && self.fee_delta == other.fee_delta
+    }
+}
+
+impl Eq for Txn {}
+
+impl Txn {
+    #[must_use]
+    pub const fn new(tx: Transaction, time: i64, fee_delta: i64) -> Self {
+        Self {
+            tx,
+            time,
+            fee_delta,
+            txid: OnceLock::new(),
+        }
+    }
+
+    /// Returns the transaction's TXID, computing it on the first call only.
+    #[must_use]
+    pub fn txid(&self) -> Txid {
+        *self.txid.get_or_init(|| self.tx.compute_txid())
+    }
+
+    /// Returns the transaction for modification, forgetting the memoized
+    /// TXID.
+    pub fn tx_mut(&mut self) -> &mut Transaction {
+        self.txid = OnceLock::new();
+        &mut self.tx
+    }
+
+    /// Returns the weight of the transaction.
+    #[must_use]
+    pub fn weight(&self) -> Weight {
+        self.tx.weight()
+    }
+
+    /// Returns the virtual size of the transaction in vbytes.
+    #[must_use]
+    pub fn vsize(&self) -> usize {
+        self.tx.vsize()
+    }
+
+    /// Computes the fee rate of the transaction including its fee delta, as
+    /// Bitcoin Core does when prioritising.
+    ///
+    /// Input values are looked up in `prevout_values`. Returns `None` if any
+    /// prevout is missing or the resulting fee would be negative.
+    #[must_use]
+    pub fn effective_feerate(&self, prevout_values: &HashMap<OutPoint, Amount>) -> Option<FeeRate> {
+        let input_sats = self.tx.input.iter().try_fold(0i64, |sum, txin| {
+            let value = prevout_values.get(&txin.previous_output)?;
+            sum.checked_add(value.to_sat().try_into().ok()?)
+        })?;
+        let output_sats = self.tx.output.iter().try_fold(0i64, |sum, txout| {
+            sum.checked_add(txout.value.to_sat().try_into().ok()?)
+        })?;
+
+        let fee = input_sats
+            .checked_sub(output_sats)?
+            .checked_add(self.fee_delta)?;
+        let fee = Amount::from_sat(u64::try_from(fee).ok()?);
+        Some(fee / self.weight())
+    }
+
+    /// Decodes a transaction followed by its entry time and fee delta.
+    fn decode<R: bitcoin::io::BufRead>(f: &mut R) -> MempoolResult<Self> {
+        let tx = Transaction::consensus_decode(f)?;
+        let time = f.read_i64()?;
+        let fee_delta = f.read_i64()?;
+
+        Ok(Self::new(tx, time, fee_delta))
+    }
+
+    fn encode(&self, buf: &mut Vec<u8>, strip_witness: bool) -> MempoolResult<()> {
+        if strip_witness {
+            // `TxIn` encodes without its witness, giving the legacy format.
+            self.tx.version.consensus_encode(buf)?;
+            self.tx.input.consensus_encode(buf)?;
+            self.tx.output.consensus_encode(buf)?;
+            self.tx.lock_time.consensus_encode(buf)?;
+        } else {
+            self.tx.consensus_encode(buf)?;
+        }
+        buf.emit_i64(self.time)?;
+        buf.emit_i64(self.fee_delta)?;
+        Ok(())
+    }
+}
+
+/// Checks that `tx` could plausibly sit in a mempool: it has inputs and
+/// outputs, is not a coinbase, spends no null prevout and has a positive
+/// version.
+///
+/// These are cheap structural checks only; scripts, amounts and prevouts
+/// are not verified.
+///
+/// # Errors
+///
+/// Returns the first [`SanityError`] found.
+pub fn basic_sanity(tx: &Transaction) -> Result<(), SanityError> {
+    if tx.input.is_empty() {
+        return Err(SanityError::NoInputs);
+    }
+    if tx.output.is_empty() {
+        return Err(SanityError::NoOutputs);
+    }
+    if tx.is_coinbase() {
+        return Err(SanityError::Coinbase);
+    }
+    if let Some(vin) = tx
+        .input
+        .iter()
+        .position(|txin| txin.previous_output.is_null())
+    {
+        return Err(SanityError::NullPrevout(vin));
+    }
+    if tx.version.0 < 1 {
+        return Err(SanityError::InvalidVersion(tx.version.0));
+    }
+    Ok(())
 }
 
-#[derive(Debug)]
+/// Returns whether `tx` signals replaceability under BIP125, that is, whether
+/// any input has an nSequence below `0xfffffffe`.
+///
+/// Only explicit signaling is detected; a transaction can also be replaceable
+/// by inheriting the signal from an unconfirmed ancestor.
+#[must_use]
+pub fn is_rbf_signaling(tx: &Transaction) -> bool {
+    tx.input.iter().any(|txin| txin.sequence.is_rbf())
+}
+
+/// On-disk order of the fee deltas and unbroadcast TXIDs, recorded while
+/// decoding so they can be written back unchanged.
+#[derive(Debug, Default, Clone)]
+struct DecodeOrder {
+    deltas: Vec<Txid>,
+    unbroadcast: Vec<Txid>,
+}
+
+#[derive(Debug, Clone)]
 pub struct MempoolSerde {
     pub version: u64,
+    pub xor_key: Option<[u8; XOR_KEY_SIZE]>,
     pub txs: Vec<Txn>,
     pub map_deltas: HashMap<Txid, i64>,
     pub unbroadcast_txids: HashSet<Txid>,
+    decode_order: DecodeOrder,
+}
+
+/// Mempools are equal when their contents are; the order fee deltas and
+/// unbroadcast TXIDs were read in is not compared.
+impl PartialEq for MempoolSerde {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.xor_key == other.xor_key
+            && self.txs == other.txs
+            && self.map_deltas == other.map_deltas
+            && self.unbroadcast_txids == other.unbroadcast_txids
+    }
+}
+
+impl Eq for MempoolSerde {}
+
+/// A short multi-line summary, unlike `Debug`, which lists every
+/// transaction.
+impl std::fmt::Display for MempoolSerde {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total_fee_delta: i128 = self.map_deltas.values().copied().map(i128::from).sum();
+        writeln!(
+            f,
+            "Mempool dump version {} ({})",
+            self.version,
+            self.core_compatibility()
+        )?;
+        writeln!(
+            f,
+            "Transactions: {} ({} vB, {} WU)",
+            self.txs.len(),
+            self.total_vsize(),
+            self.total_weight().to_wu()
+        )?;
+        writeln!(
+            f,
+            "Fee deltas:   {} (total {total_fee_delta} sat)",
+            self.map_deltas.len()
+        )?;
+        write!(f, "Unbroadcast:  {}", self.unbroadcast_txids.len())
+    }
 }
 
 impl MempoolSerde {
+    /// Creates a mempool with no transactions, fee deltas or unbroadcast
+    /// TXIDs.
+    ///
+    /// `version` should be [`MEMPOOL_DUMP_VERSION_NO_XOR_KEY`] or
+    /// [`MEMPOOL_DUMP_VERSION`]; V2 dumps are written with an all-zero XOR key
+    /// unless `xor_key` is set.
+    #[must_use]
+    pub fn empty(version: u64) -> Self {
+        Self {
+            version,
+            xor_key: None,
+            txs: Vec::new(),
+            map_deltas: HashMap::new(),
+            unbroadcast_txids: HashSet::new(),
+            decode_order: DecodeOrder::default(),
+        }
+    }
+
+    /// Adds a transaction as [`Self::add_transaction`] does, returning the
+    /// mempool so calls can be chained.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MempoolSerdeError::DuplicateTxid`] if the transaction is
+    /// already present.
+    pub fn with_transaction(
+        mut self,
+        tx: Transaction,
+        time: i64,
+        fee_delta: i64,
+    ) -> MempoolResult<Self> {
+        self.add_transaction(tx, time, fee_delta)?;
+        Ok(self)
+    }
+
     /// Creates a new `MempoolSerde` by reading and parsing a mempool.dat file.
     ///
+    /// Paths ending in `.gz` or `.zst` are decompressed first, which needs
+    /// the `gzip` or `zstd` feature respectively.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the file cannot be opened, read, or if the data
-    /// cannot be decoded as a valid mempool format.
+    /// Returns [`MempoolSerdeError::CompressionDisabled`] for a compressed
+    /// path without its feature, or an error if the file cannot be opened,
+    /// read, or if the data cannot be decoded as a valid mempool format.
     pub fn new(path: &Path) -> MempoolResult<Self> {
-        let mut f = BufReader::new(File::open(path).map_err(MempoolSerdeError::Io)?);
+        Self::new_with_progress(path, |_, _| {})
+    }
 
-        // Fetch the version as it determines if we have XOR bytes or not.
-        let version = f.read_u64()?;
+    /// Like [`Self::new`], but calls `progress(decoded, total)` after each
+    /// transaction is decoded, as [`Self::from_reader_with_progress`] does.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error [`Self::new`] can return.
+    pub fn new_with_progress<F: FnMut(u64, u64)>(path: &Path, progress: F) -> MempoolResult<Self> {
+        let file = File::open(path).map_err(MempoolSerdeError::Io)?;
+        match Compression::from_path(path) {
+            Compression::None => {
+                let len = file.metadata().map_err(MempoolSerdeError::Io)?.len();
+                Self::decode(file, Some(len), DEFAULT_MAX_TX_COUNT, progress)
+            }
+            compression => Self::decode(
+                compression.decoder(file)?,
+                None,
+                DEFAULT_MAX_TX_COUNT,
+                progress,
+            ),
+        }
+    }
 
-        let mut txs: Vec<Txn> = vec![];
-        let mut map_deltas: HashMap<Txid, i64> = HashMap::new();
-        let mut unbroadcast_txids: HashSet<Txid> = HashSet::new();
-
-        match version {
-            MEMPOOL_DUMP_VERSION_NO_XOR_KEY => {
-                // Bytes 9-16 (Number of TXNs)
-                for _ in 0..f.read_u64()? {
-                    let tx = Transaction::consensus_decode(&mut f)?;
-                    let time = f.read_i64()?;
-                    let fee_delta = f.read_i64()?;
-
-                    txs.push(Txn {
-                        tx,
-                        time,
-                        fee_delta,
-                    });
-                }
+    /// Creates a new `MempoolSerde` by parsing an in-memory mempool.dat image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data cannot be decoded as a valid mempool format.
+    pub fn from_bytes(bytes: &[u8]) -> MempoolResult<Self> {
+        Self::decode(
+            bytes,
+            Some(bytes.len() as u64),
+            DEFAULT_MAX_TX_COUNT,
+            |_, _| {},
+        )
+    }
 
-                // List of fee deltas
-                for _ in 0..VarInt::consensus_decode(&mut f)?.0 {
-                    let txid = Txid::consensus_decode(&mut f)?;
-                    let delta = f.read_i64()?;
-                    map_deltas.insert(txid, delta);
-                }
+    /// Creates a new `MempoolSerde` by parsing mempool.dat data from `reader`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader fails or if the data cannot be decoded
+    /// as a valid mempool format.
+    pub fn from_reader<R: Read>(reader: R) -> MempoolResult<Self> {
+        Self::decode(reader, None, DEFAULT_MAX_TX_COUNT, |_, _| {})
+    }
+
+    /// Like [`Self::from_reader`], but rejects dumps declaring more than
+    /// `max_tx_count` transactions instead of [`DEFAULT_MAX_TX_COUNT`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MempoolSerdeError::CorruptFile`] if the declared count is
+    /// above `max_tx_count`, or any error [`Self::from_reader`] can return.
+    pub fn from_reader_with_max_tx_count<R: Read>(
+        reader: R,
+        max_tx_count: u64,
+    ) -> MempoolResult<Self> {
+        Self::decode(reader, None, max_tx_count, |_, _| {})
+    }
+
+    /// Like [`Self::from_reader`], but calls `progress(decoded, total)` after
+    /// each transaction is decoded.
+    ///
+    /// `total` is the count declared in the dump's header, so it is exact
+    /// from the first call.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error [`Self::from_reader`] can return.
+    pub fn from_reader_with_progress<R: Read, F: FnMut(u64, u64)>(
+        reader: R,
+        progress: F,
+    ) -> MempoolResult<Self> {
+        Self::decode(reader, None, DEFAULT_MAX_TX_COUNT, progress)
+    }
+
+    /// Like [`Self::from_reader`], but salvages what it can from a dump with a
+    /// corrupt or truncated tail, such as one copied while Bitcoin Core was
+    /// still writing it.
+    ///
+    /// Transactions are decoded until the first one that cannot be, which is
+    /// skipped along with everything after it; the fee deltas and unbroadcast
+    /// TXIDs follow the transactions, so they are lost too. Each loss is
+    /// described by a [`DecodeWarning`], and an empty list means the dump
+    /// decoded as [`Self::from_reader`] would have.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the header or transaction count cannot be read or
+    /// is invalid, as there is then nothing to salvage.
+    pub fn from_reader_lenient<R: Read>(reader: R) -> MempoolResult<(Self, Vec<DecodeWarning>)> {
+        let Payload {
+            version,
+            xor_key,
+            mut reader,
+            ..
+        } = Payload::open(reader)?;
+        let f = &mut reader;
 
-                // List of unbroadcast TXIDs
-                for _ in 0..VarInt::consensus_decode(&mut f)?.0 {
-                    let txid = Txid::consensus_decode(&mut f)?;
-                    unbroadcast_txids.insert(txid);
+        let tx_count = f.read_u64()?;
+        check_tx_count(tx_count, DEFAULT_MAX_TX_COUNT, None)?;
+
+        let mut mempool = Self {
+            xor_key,
+            ..Self::empty(version)
+        };
+        let mut warnings = Vec::new();
+        for index in 0..tx_count {
+            match Txn::decode(f) {
+                Ok(txn) => mempool.txs.push(txn),
+                Err(err) => {
+                    warnings.push(DecodeWarning::SkippedTransactions {
+                        index,
+                        skipped: tx_count - index,
+                        reason: err.to_string(),
+                    });
+                    warnings.push(DecodeWarning::SkippedTail(
+                        "fee deltas and unbroadcast TXIDs follow the skipped transactions and \
+                         were not read"
+                            .to_string(),
+                    ));
+                    return Ok((mempool, warnings));
                 }
             }
-            _ => unimplemented!("Currently V2 (XOR'd) mempool backups are not decodable."),
         }
 
-        Ok(Self {
+        if let Err(err) = mempool.read_tail(f) {
+            warnings.push(DecodeWarning::SkippedTail(err.to_string()));
+        }
+        Ok((mempool, warnings))
+    }
+
+    /// Decodes a dump of `len` bytes, when known, from `reader`, reporting
+    /// each decoded transaction to `progress`.
+    ///
+    /// The declared transaction count is first checked with
+    /// [`check_tx_count`].
+    fn decode<R: Read>(
+        reader: R,
+        len: Option<u64>,
+        max_tx_count: u64,
+        mut progress: impl FnMut(u64, u64),
+    ) -> MempoolResult<Self> {
+        let Payload {
             version,
+            xor_key,
+            header_len,
+            reader,
+        } = Payload::open(reader)?;
+        let f = &mut bitcoin::io::FromStd::new(CountingReader::new(reader));
+
+        // Number of TXNs (bytes 9-16 in V1 dumps)
+        let tx_count = f.read_u64()?;
+        check_tx_count(
+            tx_count,
+            max_tx_count,
+            len.map(|len| len.saturating_sub(header_len + 8)),
+        )?;
+
+        let mut txs: Vec<Txn> = vec![];
+        for (index, decoded) in (1..=tx_count).enumerate() {
+            let offset = header_len + f.inner().consumed;
+            txs.push(Txn::decode(f).map_err(|err| err.at(index, offset))?);
+            progress(decoded, tx_count);
+        }
+
+        Self::decode_tail(f, version, xor_key, txs)
+    }
+
+    /// Decodes the fee deltas and unbroadcast TXIDs that follow the
+    /// transactions, completing the mempool.
+    fn decode_tail<R: bitcoin::io::BufRead>(
+        f: &mut R,
+        version: u64,
+        xor_key: Option<[u8; XOR_KEY_SIZE]>,
+        txs: Vec<Txn>,
+    ) -> MempoolResult<Self> {
+        let mut mempool = Self {
+            xor_key,
             txs,
-            map_deltas,
-            unbroadcast_txids,
-        })
+            ..Self::empty(version)
+        };
+        mempool.read_tail(f)?;
+        Ok(mempool)
     }
 
-    /// Serializes the mempool data to a byte vector.
+    /// Reads the fee deltas and unbroadcast TXIDs into this mempool. Entries
+    /// read before an error are kept.
+    fn read_tail<R: bitcoin::io::BufRead>(&mut self, f: &mut R) -> MempoolResult<()> {
+        // List of fee deltas
+        for _ in 0..VarInt::consensus_decode(f)?.0 {
+            let txid = Txid::consensus_decode(f)?;
+            let delta = f.read_i64()?;
+            self.map_deltas.insert(txid, delta);
+            self.decode_order.deltas.push(txid);
+        }
+
+        // List of unbroadcast TXIDs
+        for _ in 0..VarInt::consensus_decode(f)?.0 {
+            let txid = Txid::consensus_decode(f)?;
+            self.unbroadcast_txids.insert(txid);
+            self.decode_order.unbroadcast.push(txid);
+        }
+
+        Ok(())
+    }
+
+    /// Reads the number of transactions in a mempool.dat file without decoding
+    /// any of them. Compressed files are handled as in [`Self::new`].
     ///
     /// # Errors
     ///
-    /// Returns an error if encoding any of the data fails.
-    pub fn to_bytes(&self) -> MempoolResult<Vec<u8>> {
-        let mut buf = Vec::new();
+    /// Returns an error if the file cannot be opened or its header cannot be
+    /// decoded.
+    pub fn peek_count(path: &Path) -> MempoolResult<u64> {
+        let mut payload = Payload::open(Compression::open(path)?)?;
+        Ok(payload.reader.read_u64()?)
+    }
 
-        buf.emit_u64(self.version)?;
-        buf.emit_u64(self.txs.len() as u64)?;
+    /// Decodes the transactions of a mempool.dat file one at a time, passing
+    /// each to `f` without holding the whole mempool in memory.
+    ///
+    /// Decoding stops early when `f` returns [`ControlFlow::Break`]. Fee deltas
+    /// and unbroadcast TXIDs are not read. Compressed files are handled as in
+    /// [`Self::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened, read, or if a
+    /// transaction cannot be decoded.
+    pub fn stream<F: FnMut(Txn) -> ControlFlow<()>>(path: &Path, mut f: F) -> MempoolResult<()> {
+        let mut payload = Payload::open(Compression::open(path)?)?;
+        let reader = &mut payload.reader;
 
-        for txn in &self.txs {
-            txn.tx.consensus_encode(&mut buf)?;
-            buf.emit_i64(txn.time)?;
-            buf.emit_i64(txn.fee_delta)?;
+        for _ in 0..reader.read_u64()? {
+            if f(Txn::decode(reader)?).is_break() {
+                break;
+            }
         }
 
-        VarInt(self.map_deltas.len() as u64).consensus_encode(&mut buf)?;
-        for (txid, delta) in &self.map_deltas {
-            txid.consensus_encode(&mut buf)?;
-            buf.emit_i64(*delta)?;
+        Ok(())
+    }
+
+    /// Appends a transaction to the mempool and returns its computed TXID.
+    ///
+    /// A non-zero `fee_delta` is also recorded in `map_deltas` so the two stay
+    /// consistent, as Bitcoin Core expects.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MempoolSerdeError::Sanity`] if `tx` fails [`basic_sanity`],
+    /// or [`MempoolSerdeError::DuplicateTxid`] if a transaction with the same
+    /// TXID is already present, leaving the mempool unchanged.
+    pub fn add_transaction(
+        &mut self,
+        tx: Transaction,
+        time: i64,
+        fee_delta: i64,
+    ) -> MempoolResult<Txid> {
+        basic_sanity(&tx)?;
+        let txid = tx.compute_txid();
+        if self.contains_txid(&txid) {
+            return Err(MempoolSerdeError::DuplicateTxid(txid));
+        }
+        if fee_delta != 0 {
+            self.map_deltas.insert(txid, fee_delta);
         }
+        self.txs.push(Txn {
+            txid: OnceLock::from(txid),
+            ..Txn::new(tx, time, fee_delta)
+        });
+        Ok(txid)
+    }
 
-        VarInt(self.unbroadcast_txids.len() as u64).consensus_encode(&mut buf)?;
-        for txid in &self.unbroadcast_txids {
-            txid.consensus_encode(&mut buf)?;
+    /// Removes the transaction with the given TXID, along with any fee delta
+    /// or unbroadcast entry for it.
+    ///
+    /// Returns the removed transaction, or `None` if no transaction matched.
+    pub fn remove_transaction(&mut self, txid: &Txid) -> Option<Txn> {
+        let i = self.txs.iter().position(|txn| txn.txid() == *txid)?;
+        self.map_deltas.remove(txid);
+        self.unbroadcast_txids.remove(txid);
+        Some(self.txs.remove(i))
+    }
+
+    /// Sets the fee delta of the transaction with the given TXID, keeping
+    /// `map_deltas` in sync (a zero delta removes the entry).
+    ///
+    /// Returns the previous fee delta, or `None` if no transaction matched.
+    pub fn set_fee_delta(&mut self, txid: &Txid, fee_delta: i64) -> Option<i64> {
+        let txn = self.txs.iter_mut().find(|txn| txn.txid() == *txid)?;
+        let previous = std::mem::replace(&mut txn.fee_delta, fee_delta);
+
+        if fee_delta == 0 {
+            self.map_deltas.remove(txid);
+        } else {
+            self.map_deltas.insert(*txid, fee_delta);
+        }
+        Some(previous)
+    }
+
+    /// Sets the fee delta for `txid`, mirroring Bitcoin Core's
+    /// `prioritisetransaction` RPC.
+    ///
+    /// Unlike [`Self::set_fee_delta`], the delta is recorded in `map_deltas`
+    /// even if the transaction is not in `txs`, as Core keeps deltas for
+    /// transactions it has yet to see. A matching `Txn` is updated too, and a
+    /// delta of 0 removes the entry.
+    pub fn prioritise(&mut self, txid: &Txid, delta: i64) {
+        if self.set_fee_delta(txid, delta).is_some() {
+            return;
+        }
+        if delta == 0 {
+            self.map_deltas.remove(txid);
+        } else {
+            self.map_deltas.insert(*txid, delta);
         }
+    }
 
-        Ok(buf)
+    /// Empties `unbroadcast_txids`, so a node loading the dump does not
+    /// re-announce its transactions. Returns how many TXIDs were removed.
+    pub fn clear_unbroadcast(&mut self) -> usize {
+        let cleared = self.unbroadcast_txids.len();
+        self.unbroadcast_txids.clear();
+        cleared
     }
 
-    /// Writes the mempool data to a file at the specified path.
+    /// Empties `map_deltas` and zeroes every transaction's `fee_delta`, so a
+    /// node loading the dump applies no prioritisation. Returns how many
+    /// TXIDs had a delta in either place.
+    pub fn clear_deltas(&mut self) -> usize {
+        let mut cleared: HashSet<Txid> = self.map_deltas.keys().copied().collect();
+        self.map_deltas.clear();
+        for txn in &mut self.txs {
+            if txn.fee_delta != 0 {
+                cleared.insert(txn.txid());
+                txn.fee_delta = 0;
+            }
+        }
+        cleared.len()
+    }
+
+    /// Adds `other`'s transactions, fee deltas and unbroadcast TXIDs to this
+    /// mempool, resolving TXIDs present in both according to `on_conflict`.
+    ///
+    /// The merged version is the higher of the two, and `other`'s XOR key is
+    /// adopted if this mempool has none.
+    pub fn merge(&mut self, other: Self, on_conflict: MergePolicy) -> MergeReport {
+        let mut report = MergeReport::default();
+        let mut index: HashMap<Txid, usize> = self
+            .iter_with_txid()
+            .enumerate()
+            .map(|(i, (txid, _))| (txid, i))
+            .collect();
+
+        for txn in other.txs {
+            let txid = txn.txid();
+            match (index.get(&txid), on_conflict) {
+                (None, _) => {
+                    index.insert(txid, self.txs.len());
+                    self.txs.push(txn);
+                    report.added += 1;
+                }
+                (Some(_), MergePolicy::KeepExisting) => report.skipped += 1,
+                (Some(&i), MergePolicy::PreferOther) => {
+                    self.txs[i] = txn;
+                    report.overwritten += 1;
+                }
+            }
+        }
+
+        for (txid, delta) in other.map_deltas {
+            match on_conflict {
+                MergePolicy::KeepExisting => {
+                    self.map_deltas.entry(txid).or_insert(delta);
+                }
+                MergePolicy::PreferOther => {
+                    self.map_deltas.insert(txid, delta);
+                }
+            }
+        }
+
+        self.unbroadcast_txids.extend(other.unbroadcast_txids);
+        self.version = self.version.max(other.version);
+        self.xor_key = self.xor_key.or(other.xor_key);
+
+        report
+    }
+
+    /// Removes transactions whose [`Txn::effective_feerate`] is below `min`,
+    /// along with their fee deltas and unbroadcast entries.
+    ///
+    /// Transactions with prevouts missing from `prevouts` are kept when
+    /// `keep_unknown` is set and removed otherwise. Returns the number of
+    /// transactions removed.
+    pub fn retain_by_feerate(
+        &mut self,
+        min: FeeRate,
+        prevouts: &HashMap<OutPoint, Amount>,
+        keep_unknown: bool,
+    ) -> usize {
+        self.retain_txs(|txn| {
+            txn.effective_feerate(prevouts)
+                .map_or(keep_unknown, |feerate| feerate >= min)
+        })
+    }
+
+    /// Removes transactions Bitcoin Core would expire on load, along with
+    /// their fee deltas and unbroadcast entries, and returns how many were
+    /// removed.
+    ///
+    /// Like Core, a transaction is kept only if its `time` is after
+    /// `now - max_age_secs`; see [`DEFAULT_MEMPOOL_EXPIRY_SECS`] for Core's
+    /// default `-mempoolexpiry`.
+    pub fn retain_by_age(&mut self, now: i64, max_age_secs: i64) -> usize {
+        let cutoff = now.saturating_sub(max_age_secs);
+        self.retain_txs(|txn| txn.time > cutoff)
+    }
+
+    /// Removes transactions for which `keep` returns false, along with their
+    /// fee deltas and unbroadcast entries.
+    fn retain_txs(&mut self, mut keep: impl FnMut(&Txn) -> bool) -> usize {
+        let mut removed = Vec::new();
+        self.txs.retain(|txn| {
+            let keep = keep(txn);
+            if !keep {
+                removed.push(txn.txid());
+            }
+            keep
+        });
+
+        for txid in &removed {
+            self.map_deltas.remove(txid);
+            self.unbroadcast_txids.remove(txid);
+        }
+        removed.len()
+    }
+
+    /// Returns which Bitcoin Core releases write and can load this dump's
+    /// format, based on `version`.
+    #[must_use]
+    pub const fn core_compatibility(&self) -> CoreCompat {
+        match self.version {
+            MEMPOOL_DUMP_VERSION_NO_XOR_KEY => CoreCompat::PreV28,
+            MEMPOOL_DUMP_VERSION => CoreCompat::V28OrLater,
+            version => CoreCompat::Unknown(version),
+        }
+    }
+
+    /// Returns the key the dump is obfuscated with when written, or `None`
+    /// for a V1 dump, which is never obfuscated even if `xor_key` is set.
+    #[must_use]
+    pub fn xor_key(&self) -> Option<&[u8; XOR_KEY_SIZE]> {
+        self.xor_key
+            .as_ref()
+            .filter(|_| self.version == MEMPOOL_DUMP_VERSION)
+    }
+
+    /// Switches the dump to `version`, which takes `xor_key` for V2 and no key
+    /// for V1, as [`Self::upgrade_to_v2`] and [`Self::downgrade_to_v1`] do.
     ///
     /// # Errors
     ///
-    /// Returns an error if the file cannot be created or written to,
-    /// or if serialization fails.
-    pub fn write_to_file(&self, path: &Path) -> MempoolResult<()> {
-        let bytes = self.to_bytes()?;
-        let mut file = File::create(path).map_err(MempoolSerdeError::Io)?;
-        file.write_all(&bytes).map_err(MempoolSerdeError::Io)?;
+    /// Returns [`MempoolSerdeError::UnsupportedVersion`] for any other
+    /// version, or [`MempoolSerdeError::XorKeyMismatch`] if a key is missing
+    /// for V2 or given for V1. The mempool is unchanged on error.
+    pub const fn set_version(
+        &mut self,
+        version: u64,
+        xor_key: Option<[u8; XOR_KEY_SIZE]>,
+    ) -> MempoolResult<()> {
+        match (version, xor_key) {
+            (MEMPOOL_DUMP_VERSION_NO_XOR_KEY, None) => self.downgrade_to_v1(),
+            (MEMPOOL_DUMP_VERSION, Some(xor_key)) => self.upgrade_to_v2(xor_key),
+            (MEMPOOL_DUMP_VERSION_NO_XOR_KEY | MEMPOOL_DUMP_VERSION, _) => {
+                return Err(MempoolSerdeError::XorKeyMismatch(version));
+            }
+            _ => return Err(MempoolSerdeError::UnsupportedVersion(version)),
+        }
         Ok(())
     }
-}
 
-use thiserror::Error;
+    /// Switches the dump to the V2 format, obfuscated with `xor_key` when
+    /// written. A V2 dump is re-keyed.
+    pub const fn upgrade_to_v2(&mut self, xor_key: [u8; XOR_KEY_SIZE]) {
+        self.version = MEMPOOL_DUMP_VERSION;
+        self.xor_key = Some(xor_key);
+    }
 
-#[derive(Error, Debug)]
-pub enum MempoolSerdeError {
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+    /// Switches the dump to the V1 format, dropping the XOR key so it is
+    /// written unobfuscated for Bitcoin Core releases before 28.0.
+    pub const fn downgrade_to_v1(&mut self) {
+        self.version = MEMPOOL_DUMP_VERSION_NO_XOR_KEY;
+        self.xor_key = None;
+    }
 
-    #[error("Decode error: {0}")]
-    Decode(#[from] bitcoin::consensus::encode::Error),
+    /// Reorders `txs` in place by `cmp`, so the new order is what gets
+    /// written. The sort is stable: equal transactions keep their relative
+    /// order.
+    pub fn sort_by<F: FnMut(&Txn, &Txn) -> Ordering>(&mut self, cmp: F) {
+        self.txs.sort_by(cmp);
+    }
 
-    #[error("Bitcoin IO error: {0}")]
-    BitcoinIo(#[from] bitcoin::io::Error),
-}
+    /// Sorts `txs` oldest first by entry `time`.
+    pub fn sort_by_time(&mut self) {
+        self.txs.sort_by_key(|txn| txn.time);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Sorts `txs` by `fee_delta`, most prioritised first.
+    pub fn sort_by_fee_delta(&mut self) {
+        self.txs.sort_by_key(|txn| Reverse(txn.fee_delta));
+    }
 
-    #[test]
-    fn parse_v1_vector() {
-        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
-        assert_eq!(mempool.version, 1);
-        assert_ne!(mempool.version, 2);
+    /// Returns the transaction with the earliest entry `time`, or `None` if
+    /// the mempool is empty. Ties go to the first in file order.
+    #[must_use]
+    pub fn oldest(&self) -> Option<&Txn> {
+        self.txs.iter().min_by_key(|txn| txn.time)
     }
 
-    #[test]
-    fn roundtrip_serialization() {
-        use bitcoin::hashes::{Hash, sha256};
+    /// Returns the transaction with the latest entry `time`, or `None` if the
+    /// mempool is empty. Ties go to the first in file order.
+    #[must_use]
+    pub fn newest(&self) -> Option<&Txn> {
+        self.txs.iter().rev().max_by_key(|txn| txn.time)
+    }
 
-        let original_bytes = std::fs::read("./test/mempool_t4_v1_001.dat").unwrap();
-        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
-        let serialized_bytes = mempool.to_bytes().unwrap();
-        let original_hash = sha256::Hash::hash(&original_bytes);
-        let serialized_hash = sha256::Hash::hash(&serialized_bytes);
+    /// Returns the transaction with the largest `fee_delta`, or `None` if the
+    /// mempool is empty. Ties go to the first in file order.
+    #[must_use]
+    pub fn highest_fee_delta(&self) -> Option<&Txn> {
+        self.txs.iter().rev().max_by_key(|txn| txn.fee_delta)
+    }
 
-        assert_eq!(original_hash, serialized_hash, "SHA256 hashes don't match");
+    /// Returns the combined virtual size of all transactions in vbytes.
+    #[must_use]
+    pub fn total_vsize(&self) -> usize {
+        self.txs.iter().map(Txn::vsize).sum()
+    }
+
+    /// Returns the combined weight of all transactions.
+    #[must_use]
+    pub fn total_weight(&self) -> Weight {
+        self.txs.iter().map(Txn::weight).sum()
+    }
+
+    /// Returns the combined value of every transaction's outputs.
+    ///
+    /// This is not what the mempool is worth: fees are left out and outputs
+    /// spent by other transactions in the dump are counted too. It is a quick
+    /// sanity check, as synthetic dumps tend to give themselves away with
+    /// absurd totals.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MempoolSerdeError::ValueOverflow`] if the total does not fit
+    /// in an [`Amount`].
+    pub fn total_output_value(&self) -> MempoolResult<Amount> {
+        self.txs
+            .iter()
+            .flat_map(|txn| &txn.tx.output)
+            .try_fold(Amount::ZERO, |total, out| total.checked_add(out.value))
+            .ok_or(MempoolSerdeError::ValueOverflow)
+    }
+
+    /// Counts transactions by fee delta, in buckets of `bucket_size` sats keyed
+    /// by their lower bound (so `0` holds deltas in `0..bucket_size`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket_size` is not positive.
+    #[must_use]
+    pub fn fee_delta_histogram(&self, bucket_size: i64) -> BTreeMap<i64, usize> {
+        assert!(bucket_size > 0, "bucket_size must be positive");
+        let mut histogram = BTreeMap::new();
+        for txn in &self.txs {
+            let bucket = txn.fee_delta.div_euclid(bucket_size) * bucket_size;
+            *histogram.entry(bucket).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Returns whether a transaction with the given TXID is in `txs`.
+    #[must_use]
+    pub fn contains_txid(&self, txid: &Txid) -> bool {
+        self.get(txid).is_some()
+    }
+
+    /// Returns the transaction with the given TXID.
+    ///
+    /// This is a linear scan, as `txs` is public and an index could go stale;
+    /// build a map from [`Self::iter_with_txid`] for many lookups.
+    #[must_use]
+    pub fn get(&self, txid: &Txid) -> Option<&Txn> {
+        self.iter_with_txid()
+            .find_map(|(candidate, txn)| (candidate == *txid).then_some(txn))
+    }
+
+    /// Returns the TXIDs that appear more than once in `txs`, each with the
+    /// indices of all its copies, in order of first appearance. Bitcoin Core
+    /// only loads the first copy.
+    #[must_use]
+    pub fn duplicates(&self) -> Vec<(Txid, Vec<usize>)> {
+        let mut copies: HashMap<Txid, Vec<usize>> = HashMap::new();
+        for (i, (txid, _)) in self.iter_with_txid().enumerate() {
+            copies.entry(txid).or_default().push(i);
+        }
+        let mut duplicated: Vec<(Txid, Vec<usize>)> = copies
+            .into_iter()
+            .filter(|(_, indices)| indices.len() > 1)
+            .collect();
+        duplicated.sort_unstable_by_key(|(_, indices)| indices[0]);
+        duplicated
+    }
+
+    /// Removes every copy but the first of each duplicated transaction, as
+    /// listed by [`Self::duplicates`], and returns how many were removed.
+    ///
+    /// Fee deltas and unbroadcast TXIDs are keyed by TXID, so they stay with
+    /// the copy that is kept.
+    pub fn dedup(&mut self) -> usize {
+        self.remove_duplicates().len()
+    }
+
+    /// Removes later copies of duplicated transactions, returning the TXID of
+    /// each copy removed.
+    fn remove_duplicates(&mut self) -> Vec<Txid> {
+        let mut seen = HashSet::new();
+        let mut removed = Vec::new();
+        self.txs.retain(|txn| {
+            let txid = txn.txid();
+            let first = seen.insert(txid);
+            if !first {
+                removed.push(txid);
+            }
+            first
+        });
+        removed
+    }
+
+    /// Returns the TXIDs of transactions with an output paying `script`, in
+    /// file order. Pass `address.script_pubkey()` to search for an address.
+    #[must_use]
+    pub fn spending_script(&self, script: &ScriptBuf) -> Vec<Txid> {
+        self.txs
+            .iter()
+            .filter(|txn| txn.tx.output.iter().any(|out| out.script_pubkey == *script))
+            .map(Txn::txid)
+            .collect()
+    }
+
+    /// Returns the TXIDs of transactions signaling BIP125 replaceability, in
+    /// file order. See [`is_rbf_signaling`].
+    #[must_use]
+    pub fn rbf_signaling_txids(&self) -> Vec<Txid> {
+        self.txs
+            .iter()
+            .filter(|txn| is_rbf_signaling(&txn.tx))
+            .map(Txn::txid)
+            .collect()
+    }
+
+    /// Iterates over the transactions paired with their TXIDs, each computed
+    /// once and memoized by [`Txn::txid`].
+    pub fn iter_with_txid(&self) -> impl Iterator<Item = (Txid, &Txn)> {
+        self.txs.iter().map(|txn| (txn.txid(), txn))
+    }
+
+    /// Checks that every fee delta and unbroadcast TXID refers to a transaction
+    /// in `txs`, and that no transaction appears twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns every inconsistency found if the mempool is not coherent.
+    pub fn validate(&self) -> Result<(), Vec<ValidationIssue>> {
+        let txids: HashSet<Txid> = self.txs.iter().map(Txn::txid).collect();
+
+        let issues: Vec<ValidationIssue> = self
+            .map_deltas
+            .keys()
+            .filter(|txid| !txids.contains(*txid))
+            .map(|txid| ValidationIssue::DanglingDelta(*txid))
+            .chain(
+                self.unbroadcast_txids
+                    .iter()
+                    .filter(|txid| !txids.contains(*txid))
+                    .map(|txid| ValidationIssue::UnknownUnbroadcast(*txid)),
+            )
+            .chain(
+                self.duplicates()
+                    .into_iter()
+                    .map(|(txid, _)| ValidationIssue::DuplicateTransaction(txid)),
+            )
+            .collect();
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// Drops fee deltas and unbroadcast TXIDs that refer to transactions not
+    /// in `txs`.
+    ///
+    /// When `rederive_deltas` is set, `map_deltas` is also brought in line
+    /// with each transaction's non-zero `fee_delta`. When `dedup` is set,
+    /// only the first copy of a duplicated transaction is kept.
+    pub fn repair(&mut self, rederive_deltas: bool, dedup: bool) -> RepairReport {
+        let mut report = RepairReport::default();
+
+        for issue in self.validate().err().unwrap_or_default() {
+            match issue {
+                ValidationIssue::DanglingDelta(txid) => {
+                    self.map_deltas.remove(&txid);
+                    report.removed_deltas.push(txid);
+                }
+                ValidationIssue::UnknownUnbroadcast(txid) => {
+                    self.unbroadcast_txids.remove(&txid);
+                    report.removed_unbroadcast.push(txid);
+                }
+                ValidationIssue::DuplicateTransaction(_) => {}
+            }
+        }
+
+        if dedup {
+            report.removed_duplicates = self.remove_duplicates();
+        }
+
+        if rederive_deltas {
+            for txn in self.txs.iter().filter(|txn| txn.fee_delta != 0) {
+                let txid = txn.txid();
+                if self.map_deltas.insert(txid, txn.fee_delta) != Some(txn.fee_delta) {
+                    report.added_deltas.push((txid, txn.fee_delta));
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Flags transactions whose outputs look unusual under `cfg`, one entry
+    /// per finding, in file order.
+    ///
+    /// Prevouts are not in the dump, so fees cannot be checked; these are
+    /// output-only heuristics. `OP_RETURN` outputs are never reported as zero
+    /// value or dust. Outputs whose total overflows an [`Amount`] are reported
+    /// as [`Anomaly::LargeOutputTotal`] of [`Amount::MAX`].
+    #[must_use]
+    pub fn anomalies(&self, cfg: &AnomalyConfig) -> Vec<(Txid, Anomaly)> {
+        let mut found = Vec::new();
+        for (txid, txn) in self.iter_with_txid() {
+            let outputs = &txn.tx.output;
+            if outputs.len() > cfg.max_outputs {
+                found.push((txid, Anomaly::TooManyOutputs(outputs.len())));
+            }
+
+            let total = outputs
+                .iter()
+                .map(|out| out.value)
+                .try_fold(Amount::ZERO, Amount::checked_add);
+            match total {
+                Some(total) if total <= cfg.max_total_output => {}
+                total => found.push((
+                    txid,
+                    Anomaly::LargeOutputTotal(total.unwrap_or(Amount::MAX)),
+                )),
+            }
+
+            for (vout, out) in outputs.iter().enumerate() {
+                if out.script_pubkey.is_op_return() {
+                    continue;
+                }
+                if out.value == Amount::ZERO {
+                    found.push((txid, Anomaly::ZeroValueOutput(vout)));
+                } else if out.value < cfg.dust_threshold {
+                    found.push((txid, Anomaly::DustOutput(vout, out.value)));
+                }
+            }
+        }
+        found
+    }
+
+    /// Serializes the mempool data to a byte vector.
+    ///
+    /// V2 dumps are written with their XOR key and obfuscated payload. A V2
+    /// mempool without a key is written with an all-zero key, which Bitcoin
+    /// Core accepts as "no obfuscation".
+    ///
+    /// Fee deltas and unbroadcast TXIDs read from a file keep their original
+    /// order, so decoding and re-encoding is byte-exact. Entries added since
+    /// are written after them, sorted by TXID, so the output is deterministic.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding any of the data fails.
+    pub fn to_bytes(&self) -> MempoolResult<Vec<u8>> {
+        self.encode(false)
+    }
+
+    /// Serializes the mempool like [`Self::to_bytes`], but with every
+    /// transaction in the legacy, witness-free serialization.
+    ///
+    /// This is for debugging and for consumers that cannot parse segwit.
+    /// Segwit transactions lose their signatures, so Bitcoin Core will reject
+    /// them when loading the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding any of the data fails.
+    pub fn to_bytes_stripped(&self) -> MempoolResult<Vec<u8>> {
+        self.encode(true)
+    }
+
+    fn encode(&self, strip_witness: bool) -> MempoolResult<Vec<u8>> {
+        let mut buf = Vec::new();
+
+        buf.emit_u64(self.version)?;
+
+        if self.version == MEMPOOL_DUMP_VERSION {
+            let xor_key = self.xor_key.unwrap_or_default();
+            xor_key.to_vec().consensus_encode(&mut buf)?;
+
+            let offset = buf.len();
+            self.encode_body(&mut buf, strip_witness)?;
+            xor_in_place(&mut buf[offset..], xor_key, offset);
+        } else {
+            self.encode_body(&mut buf, strip_witness)?;
+        }
+
+        Ok(buf)
+    }
+
+    /// Encodes the transactions, fee deltas and unbroadcast TXIDs that follow
+    /// the version (and XOR key, for V2 dumps).
+    fn encode_body(&self, buf: &mut Vec<u8>, strip_witness: bool) -> MempoolResult<()> {
+        buf.emit_u64(self.txs.len() as u64)?;
+
+        for txn in &self.txs {
+            txn.encode(buf, strip_witness)?;
+        }
+
+        VarInt(self.map_deltas.len() as u64).consensus_encode(buf)?;
+        for txid in encode_order(&self.decode_order.deltas, self.map_deltas.keys()) {
+            txid.consensus_encode(buf)?;
+            buf.emit_i64(self.map_deltas[txid])?;
+        }
+
+        VarInt(self.unbroadcast_txids.len() as u64).consensus_encode(buf)?;
+        for txid in encode_order(
+            &self.decode_order.unbroadcast,
+            self.unbroadcast_txids.iter(),
+        ) {
+            txid.consensus_encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the encoded mempool to `writer`, the counterpart of
+    /// [`Self::from_reader`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or writing fails.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> MempoolResult<()> {
+        writer.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Writes the mempool data to a file at the specified path.
+    ///
+    /// The data is written to a sibling `.tmp` file which is synced and then
+    /// renamed over `path`, so an existing file is never left truncated.
+    /// Paths ending in `.gz` or `.zst` are compressed as in [`Self::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MempoolSerdeError::CompressionDisabled`] for a compressed
+    /// path without its feature, or an error if the file cannot be created or
+    /// written to, or if serialization fails.
+    pub fn write_to_file(&self, path: &Path) -> MempoolResult<()> {
+        let bytes = Compression::from_path(path).compress(self.to_bytes()?)?;
+        let tmp_path = tmp_path(path)?;
+
+        let result = write_synced(&tmp_path, &bytes).and_then(|()| replace_file(&tmp_path, path));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        result.map_err(MempoolSerdeError::Io)
+    }
+
+    /// Appends `new` to the dump at `path` without loading the dump into
+    /// memory.
+    ///
+    /// The transaction count precedes the transactions and the fee deltas
+    /// follow them, so a dump cannot be extended in place. Instead it is
+    /// streamed into a sibling `.tmp` file that then replaces `path`: existing
+    /// transactions are copied one at a time, `new` is written after them, the
+    /// fee deltas gain an entry for each non-zero `fee_delta` in `new`, and
+    /// the unbroadcast TXIDs are copied unchanged. This costs a full read and
+    /// write of the file, but only holds one transaction, the fee deltas and
+    /// the TXIDs in memory.
+    ///
+    /// Compressed dumps are not supported; load them with [`Self::new`] and
+    /// save them with [`Self::write_to_file`] instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MempoolSerdeError::Sanity`] if a transaction in `new` fails
+    /// [`basic_sanity`], as [`Self::add_transaction`] does, or
+    /// [`MempoolSerdeError::DuplicateTxid`] if one is already in the dump or
+    /// repeated in `new`, leaving `path` unchanged; or an error if `path` is
+    /// compressed or the dump cannot be read, decoded or written.
+    pub fn append_transactions(path: &Path, new: &[Txn]) -> MempoolResult<()> {
+        if Compression::from_path(path) != Compression::None {
+            return Err(MempoolSerdeError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "cannot append to a compressed dump",
+            )));
+        }
+        for txn in new {
+            basic_sanity(&txn.tx)?;
+        }
+        let tmp_path = tmp_path(path)?;
+
+        let result = Self::append_to_copy(path, &tmp_path, new)
+            .and_then(|()| replace_file(&tmp_path, path).map_err(MempoolSerdeError::Io));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&tmp_path);
+        }
+        result
+    }
+
+    /// Streams the dump at `path` into `tmp_path` with `new` appended.
+    fn append_to_copy(path: &Path, tmp_path: &Path, new: &[Txn]) -> MempoolResult<()> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let Payload {
+            version,
+            xor_key,
+            header_len,
+            mut reader,
+        } = Payload::open(file)?;
+        let f = &mut reader;
+
+        let mut header = Vec::new();
+        header.emit_u64(version)?;
+        if let Some(key) = xor_key {
+            key.to_vec().consensus_encode(&mut header)?;
+        }
+        let mut file = BufWriter::new(File::create(tmp_path)?);
+        file.write_all(&header)?;
+        let mut out = XorWriter::new(
+            file,
+            xor_key,
+            usize::try_from(header_len).unwrap_or_default(),
+        );
+
+        let tx_count = f.read_u64()?;
+        check_tx_count(
+            tx_count,
+            DEFAULT_MAX_TX_COUNT,
+            Some(len.saturating_sub(header_len + 8)),
+        )?;
+        let new_count = tx_count.checked_add(new.len() as u64).ok_or_else(|| {
+            MempoolSerdeError::CorruptFile(format!(
+                "declared {tx_count} transactions, too many to append to"
+            ))
+        })?;
+        let mut buf = Vec::new();
+        buf.emit_u64(new_count)?;
+
+        let mut txids = HashSet::new();
+        for _ in 0..tx_count {
+            let txn = Txn::decode(f)?;
+            txids.insert(txn.txid());
+            txn.encode(&mut buf, false)?;
+            out.write_all(&buf)?;
+            buf.clear();
+        }
+
+        let mut new_deltas = HashMap::new();
+        for txn in new {
+            let txid = txn.txid();
+            if !txids.insert(txid) {
+                return Err(MempoolSerdeError::DuplicateTxid(txid));
+            }
+            if txn.fee_delta != 0 {
+                new_deltas.insert(txid, txn.fee_delta);
+            }
+            txn.encode(&mut buf, false)?;
+        }
+
+        // Existing deltas keep their order; the new transactions' deltas
+        // replace any recorded for them and follow, sorted by TXID.
+        let mut deltas = Vec::new();
+        for _ in 0..VarInt::consensus_decode(f)?.0 {
+            let txid = Txid::consensus_decode(f)?;
+            let delta = f.read_i64()?;
+            if !new_deltas.contains_key(&txid) {
+                deltas.push((txid, delta));
+            }
+        }
+        let mut new_deltas: Vec<(Txid, i64)> = new_deltas.into_iter().collect();
+        new_deltas.sort_unstable();
+        deltas.extend(new_deltas);
+
+        VarInt(deltas.len() as u64).consensus_encode(&mut buf)?;
+        for (txid, delta) in deltas {
+            txid.consensus_encode(&mut buf)?;
+            buf.emit_i64(delta)?;
+        }
+        out.write_all(&buf)?;
+
+        // The unbroadcast TXIDs close the dump and are copied as is.
+        std::io::copy(f, &mut out)?;
+
+        out.inner
+            .into_inner()
+            .map_err(std::io::IntoInnerError::into_error)?
+            .sync_all()?;
+        Ok(())
+    }
+}
+
+/// Creates `path`, writes `bytes` to it and syncs it to disk.
+fn write_synced(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Returns the sibling `.tmp` path a replacement for `path` is written to.
+fn tmp_path(path: &Path) -> std::io::Result<PathBuf> {
+    let mut tmp_name = path
+        .file_name()
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name")
+        })?
+        .to_os_string();
+    tmp_name.push(".tmp");
+    Ok(path.with_file_name(tmp_name))
+}
+
+/// Renames `from` over `to`, replacing any existing file.
+fn replace_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        // Renaming onto an existing file can fail on Windows, e.g. when the
+        // destination is read-only or was opened without sharing; retry once
+        // after removing it.
+        #[cfg(windows)]
+        Err(_) if to.exists() => {
+            std::fs::remove_file(to)?;
+            std::fs::rename(from, to)
+        }
+        result => result,
+    }
+}
+
+/// Returns `txids` in their `recorded` on-disk order, followed by any entries
+/// not recorded, sorted by TXID.
+fn encode_order<'a>(recorded: &[Txid], txids: impl Iterator<Item = &'a Txid>) -> Vec<&'a Txid> {
+    let mut remaining: HashSet<&Txid> = txids.collect();
+    let mut ordered: Vec<&Txid> = recorded
+        .iter()
+        .filter_map(|txid| remaining.take(txid))
+        .collect();
+
+    let mut added: Vec<&Txid> = remaining.into_iter().collect();
+    added.sort_unstable();
+    ordered.extend(added);
+    ordered
+}
+
+/// XORs `buf` with `key`, where `offset` is the absolute stream position of
+/// `buf[0]`. Bitcoin Core cycles the key from the start of the file, so the
+/// offset keeps us aligned with it.
+fn xor_in_place(buf: &mut [u8], key: [u8; XOR_KEY_SIZE], offset: usize) {
+    for (i, byte) in buf.iter_mut().enumerate() {
+        *byte ^= key[(offset + i) % XOR_KEY_SIZE];
+    }
+}
+
+/// Reader adapter that undoes the XOR obfuscation of V2 mempool dumps.
+///
+/// V1 dumps have no key, in which case bytes are passed through untouched.
+struct XorReader<R> {
+    inner: R,
+    key: Option<[u8; XOR_KEY_SIZE]>,
+    offset: usize,
+}
+
+impl<R> XorReader<R> {
+    const fn new(inner: R, key: Option<[u8; XOR_KEY_SIZE]>, offset: usize) -> Self {
+        Self { inner, key, offset }
+    }
+}
+
+impl<R: Read> Read for XorReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(key) = self.key {
+            xor_in_place(&mut buf[..n], key, self.offset);
+        }
+        self.offset += n;
+        Ok(n)
+    }
+}
+
+/// Reader adapter that counts the bytes consumed from `inner`, so decode
+/// errors can say where in the dump they occurred.
+struct CountingReader<R> {
+    inner: R,
+    consumed: u64,
+}
+
+impl<R> CountingReader<R> {
+    const fn new(inner: R) -> Self {
+        Self { inner, consumed: 0 }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.consumed += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.consumed += amt as u64;
+    }
+}
+
+/// Writes to `inner` through the XOR `key`, counting from the absolute file
+/// `offset`, to produce the obfuscated payload of a V2 dump.
+struct XorWriter<W> {
+    inner: W,
+    key: Option<[u8; XOR_KEY_SIZE]>,
+    offset: usize,
+}
+
+impl<W> XorWriter<W> {
+    const fn new(inner: W, key: Option<[u8; XOR_KEY_SIZE]>, offset: usize) -> Self {
+        Self { inner, key, offset }
+    }
+}
+
+impl<W: Write> Write for XorWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = match self.key {
+            Some(key) => {
+                let mut obfuscated = buf.to_vec();
+                xor_in_place(&mut obfuscated, key, self.offset);
+                self.inner.write(&obfuscated)?
+            }
+            None => self.inner.write(buf)?,
+        };
+        self.offset += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Checks a declared transaction count against `max_tx_count` and, when
+/// known, against how many entries the `remaining` bytes could hold, so a
+/// corrupt count fails fast instead of driving the decode loop.
+fn check_tx_count(tx_count: u64, max_tx_count: u64, remaining: Option<u64>) -> MempoolResult<()> {
+    if tx_count > max_tx_count {
+        return Err(MempoolSerdeError::CorruptFile(format!(
+            "declared {tx_count} transactions, more than the limit of {max_tx_count}"
+        )));
+    }
+    if let Some(remaining) = remaining
+        && tx_count > remaining / MIN_TXN_SIZE
+    {
+        return Err(MempoolSerdeError::CorruptFile(format!(
+            "declared {tx_count} transactions, but only {remaining} bytes remain"
+        )));
+    }
+    Ok(())
+}
+
+/// The header of a mempool dump and a reader over the de-obfuscated data
+/// that follows it.
+struct Payload<R> {
+    version: u64,
+    xor_key: Option<[u8; XOR_KEY_SIZE]>,
+    /// Length of the version and key preceding the payload.
+    header_len: u64,
+    reader: BufReader<XorReader<BufReader<R>>>,
+}
+
+impl<R: Read> Payload<R> {
+    /// Reads the version (and XOR key, for V2 dumps) from `reader`.
+    fn open(reader: R) -> MempoolResult<Self> {
+        let mut f = BufReader::new(reader);
+
+        // Fetch the version as it determines if we have XOR bytes or not.
+        let version = f.read_u64()?;
+
+        let xor_key = match version {
+            MEMPOOL_DUMP_VERSION_NO_XOR_KEY => None,
+            MEMPOOL_DUMP_VERSION => {
+                // The XOR key is stored in the clear directly after the version.
+                let key = Vec::<u8>::consensus_decode(&mut f)?;
+                let xor_key: [u8; XOR_KEY_SIZE] = key
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| MempoolSerdeError::InvalidXorKeyLength(key.len()))?;
+                Some(xor_key)
+            }
+            _ => return Err(MempoolSerdeError::UnsupportedVersion(version)),
+        };
+
+        // Bitcoin Core XORs from the absolute file position, so the key
+        // cycle starts at the offset of the first byte after the key.
+        let offset = 8 + xor_key.map_or(0, |_| VarInt(XOR_KEY_SIZE as u64).size() + XOR_KEY_SIZE);
+
+        Ok(Self {
+            version,
+            xor_key,
+            header_len: offset as u64,
+            reader: BufReader::new(XorReader::new(f, xor_key, offset)),
+        })
+    }
+}
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MempoolSerdeError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Decode error: {0}")]
+    Decode(#[from] bitcoin::consensus::encode::Error),
+
+    #[error("Bitcoin IO error: {0}")]
+    BitcoinIo(#[from] bitcoin::io::Error),
+
+    #[error("Unsupported mempool dump version: {0}")]
+    UnsupportedVersion(u64),
+
+    #[error("JSON error: {0}")]
+    Json(String),
+
+    #[error("Invalid XOR key length: {0} (expected {XOR_KEY_SIZE})")]
+    InvalidXorKeyLength(usize),
+
+    #[error("XOR key does not match version {0}: V2 dumps need one and V1 dumps take none")]
+    XorKeyMismatch(u64),
+
+    #[error("Corrupt mempool dump: {0}")]
+    CorruptFile(String),
+
+    /// A transaction failed to decode. `offset` is where its entry starts in
+    /// the dump, after decompression.
+    #[error("Transaction {index} at byte {offset}: {source}")]
+    DecodeAt {
+        index: usize,
+        offset: u64,
+        source: Box<Self>,
+    },
+
+    #[error("Output values add up to more than an amount can hold")]
+    ValueOverflow,
+
+    #[error("Transaction already in mempool: {0}")]
+    DuplicateTxid(Txid),
+
+    #[error("Compressed dumps need the `{0}` feature")]
+    CompressionDisabled(&'static str),
+
+    #[error("Transaction cannot be in a mempool: {0}")]
+    Sanity(#[from] SanityError),
+}
+
+impl MempoolSerdeError {
+    /// Wraps the error in [`Self::DecodeAt`] for the transaction at `index`,
+    /// whose entry starts at `offset`.
+    fn at(self, index: usize, offset: u64) -> Self {
+        Self::DecodeAt {
+            index,
+            offset,
+            source: Box::new(self),
+        }
+    }
+}
+
+/// How [`MempoolSerde::merge`] resolves a TXID present in both mempools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep this mempool's entry and skip the other's.
+    KeepExisting,
+    /// Replace this mempool's entry with the other's.
+    PreferOther,
+}
+
+/// Counts of transactions handled by [`MempoolSerde::merge`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeReport {
+    pub added: usize,
+    pub skipped: usize,
+    pub overwritten: usize,
+}
+
+/// Entries changed by [`MempoolSerde::repair`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RepairReport {
+    pub removed_deltas: Vec<Txid>,
+    pub removed_unbroadcast: Vec<Txid>,
+    /// One entry per copy removed by the `dedup` option.
+    pub removed_duplicates: Vec<Txid>,
+    /// Fee deltas inserted or updated from `Txn::fee_delta`.
+    pub added_deltas: Vec<(Txid, i64)>,
+}
+
+/// Thresholds for [`MempoolSerde::anomalies`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnomalyConfig {
+    /// Flag transactions with more outputs than this.
+    pub max_outputs: usize,
+    /// Flag non-zero outputs worth less than this.
+    pub dust_threshold: Amount,
+    /// Flag transactions whose outputs sum to more than this.
+    pub max_total_output: Amount,
+}
+
+impl Default for AnomalyConfig {
+    /// 1000 outputs, Bitcoin Core's 546 sat P2PKH dust limit and 1000 BTC.
+    fn default() -> Self {
+        Self {
+            max_outputs: 1_000,
+            dust_threshold: Amount::from_sat(546),
+            max_total_output: Amount::from_int_btc(1_000),
+        }
+    }
+}
+
+/// An unusual output pattern found by [`MempoolSerde::anomalies`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Anomaly {
+    #[error("{0} outputs")]
+    TooManyOutputs(usize),
+
+    #[error("Outputs total {0}")]
+    LargeOutputTotal(Amount),
+
+    #[error("Output {0} has zero value")]
+    ZeroValueOutput(usize),
+
+    #[error("Output {0} is dust ({1})")]
+    DustOutput(usize, Amount),
+}
+
+/// The Bitcoin Core releases matching a dump version, from
+/// [`MempoolSerde::core_compatibility`].
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreCompat {
+    /// V1 dumps are written by releases before 28.0 (or 28.0+ with
+    /// `-persistmempoolv1`) and load in every release.
+    #[error("Bitcoin Core <28.0 (v1, no XOR)")]
+    PreV28,
+
+    /// V2 dumps are written by 28.0 and later and do not load in older
+    /// releases.
+    #[error("Bitcoin Core >=28.0 (v2, XOR key)")]
+    V28OrLater,
+
+    /// A version no known release writes; saving it is likely to fail.
+    #[error("Unknown dump version {0}")]
+    Unknown(u64),
+}
+
+/// Why [`basic_sanity`] rejected a transaction.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SanityError {
+    #[error("No inputs")]
+    NoInputs,
+
+    #[error("No outputs")]
+    NoOutputs,
+
+    #[error("Coinbase transaction")]
+    Coinbase,
+
+    #[error("Input {0} spends a null prevout")]
+    NullPrevout(usize),
+
+    #[error("Invalid version {0}")]
+    InvalidVersion(i32),
+}
+
+/// Data [`MempoolSerde::from_reader_lenient`] could not decode and skipped.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DecodeWarning {
+    #[error("Skipped {skipped} transaction(s) from index {index}: {reason}")]
+    SkippedTransactions {
+        /// Position of the first undecodable transaction.
+        index: u64,
+        /// The undecodable transaction and those declared after it.
+        skipped: u64,
+        reason: String,
+    },
+
+    /// Some or all of the fee deltas and unbroadcast TXIDs were lost; those
+    /// read before the error are kept.
+    #[error("Fee deltas and unbroadcast TXIDs cut short: {0}")]
+    SkippedTail(String),
+}
+
+/// An inconsistency between `txs` and the fee delta or unbroadcast sets.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    #[error("Fee delta for unknown transaction {0}")]
+    DanglingDelta(Txid),
+
+    #[error("Unbroadcast TXID for unknown transaction {0}")]
+    UnknownUnbroadcast(Txid),
+
+    #[error("Transaction {0} appears more than once")]
+    DuplicateTransaction(Txid),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_v1_vector() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        assert_eq!(mempool.version, 1);
+        assert_ne!(mempool.version, 2);
+    }
+
+    #[test]
+    fn parse_v2_vector() {
+        let v1 = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let v2 = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        assert_eq!(v2.version, 2);
+        assert_eq!(v1.txs.len(), v2.txs.len());
+
+        for (a, b) in v1.txs.iter().zip(&v2.txs) {
+            assert_eq!(a.tx, b.tx);
+            assert_eq!(a.time, b.time);
+            assert_eq!(a.fee_delta, b.fee_delta);
+        }
+        assert_eq!(v1.map_deltas, v2.map_deltas);
+        assert_eq!(v1.unbroadcast_txids, v2.unbroadcast_txids);
+    }
+
+    #[test]
+    fn core_compatibility_follows_version() {
+        let v1 = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let v2 = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        assert_eq!(v1.core_compatibility(), CoreCompat::PreV28);
+        assert_eq!(v2.core_compatibility(), CoreCompat::V28OrLater);
+        assert_eq!(
+            MempoolSerde::empty(7).core_compatibility(),
+            CoreCompat::Unknown(7)
+        );
+        assert_eq!(
+            v2.core_compatibility().to_string(),
+            "Bitcoin Core >=28.0 (v2, XOR key)"
+        );
+    }
+
+    #[test]
+    fn roundtrip_serialization() {
+        use bitcoin::hashes::{Hash, sha256};
+
+        let original_bytes = std::fs::read("./test/mempool_t4_v1_001.dat").unwrap();
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let serialized_bytes = mempool.to_bytes().unwrap();
+        let original_hash = sha256::Hash::hash(&original_bytes);
+        let serialized_hash = sha256::Hash::hash(&serialized_bytes);
+
+        assert_eq!(original_hash, serialized_hash, "SHA256 hashes don't match");
+    }
+
+    #[test]
+    fn roundtrip_serialization_v2() {
+        use bitcoin::hashes::{Hash, sha256};
+
+        let original_bytes = std::fs::read("./test/mempool_t4_v2_001.dat").unwrap();
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        assert!(mempool.xor_key.is_some());
+
+        let serialized_bytes = mempool.to_bytes().unwrap();
+        let original_hash = sha256::Hash::hash(&original_bytes);
+        let serialized_hash = sha256::Hash::hash(&serialized_bytes);
+
+        assert_eq!(original_hash, serialized_hash, "SHA256 hashes don't match");
+    }
+
+    #[test]
+    fn add_transaction_records_delta() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let tx = mempool.txs.remove(0).tx;
+        let expected = tx.compute_txid();
+        let len = mempool.txs.len();
+
+        let txid = mempool
+            .add_transaction(tx.clone(), 1_700_000_000, 1_000)
+            .unwrap();
+        assert_eq!(txid, expected);
+        assert_eq!(mempool.txs.len(), len + 1);
+        assert_eq!(mempool.map_deltas.get(&txid), Some(&1_000));
+
+        assert!(matches!(
+            mempool.add_transaction(tx, 1_700_000_001, 2_000),
+            Err(MempoolSerdeError::DuplicateTxid(dup)) if dup == txid
+        ));
+        assert_eq!(mempool.txs.len(), len + 1);
+        assert_eq!(mempool.map_deltas.get(&txid), Some(&1_000));
+    }
+
+    #[test]
+    fn upgrade_to_v2_obfuscates_with_the_key() {
+        let v1 = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let key = [0x5a, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+        let mut v2 = v1.clone();
+        v2.upgrade_to_v2(key);
+        assert_eq!(v2.core_compatibility(), CoreCompat::V28OrLater);
+
+        let bytes = v2.to_bytes().unwrap();
+        // Version, then the key as a length-prefixed vector.
+        assert_eq!(bytes[..8], MEMPOOL_DUMP_VERSION.to_le_bytes());
+        assert_eq!(bytes[8..17], [&[8][..], &key].concat());
+        let decoded = MempoolSerde::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.xor_key, Some(key));
+        assert_eq!(decoded.txs, v1.txs);
+    }
+
+    #[test]
+    fn clear_unbroadcast_reports_the_count() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let txids: Vec<Txid> = mempool.txs[..3].iter().map(Txn::txid).collect();
+        mempool.unbroadcast_txids.extend(txids);
+        assert_eq!(mempool.clear_unbroadcast(), 3);
+        assert!(mempool.unbroadcast_txids.is_empty());
+        assert_eq!(mempool.clear_unbroadcast(), 0);
+    }
+
+    #[test]
+    fn decode_errors_locate_the_bad_transaction() {
+        let mut bytes = std::fs::read("./test/mempool_t4_v1_001.dat").unwrap();
+        let mempool = MempoolSerde::from_bytes(&bytes).unwrap();
+        let offset = 16
+            + mempool.txs[..5]
+                .iter()
+                .map(|txn| bitcoin::consensus::serialize(&txn.tx).len() as u64 + 16)
+                .sum::<u64>();
+
+        // An empty input list followed by an unknown segwit flag.
+        let start = usize::try_from(offset).unwrap();
+        bytes[start + 4..start + 6].copy_from_slice(&[0, 2]);
+        let err = MempoolSerde::from_reader(bytes.as_slice()).unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                MempoolSerdeError::DecodeAt { index: 5, offset: at, source }
+                    if *at == offset && matches!(**source, MempoolSerdeError::Decode(_))
+            ),
+            "{err}"
+        );
+        assert!(
+            err.to_string()
+                .starts_with(&format!("Transaction 5 at byte {offset}: "))
+        );
+    }
+
+    #[test]
+    fn lenient_decode_salvages_a_truncated_dump() {
+        let bytes = std::fs::read("./test/mempool_t4_v2_001.dat").unwrap();
+        let full = MempoolSerde::from_bytes(&bytes).unwrap();
+
+        let (mempool, warnings) = MempoolSerde::from_reader_lenient(bytes.as_slice()).unwrap();
+        assert_eq!(mempool, full);
+        assert!(warnings.is_empty());
+
+        let (mempool, warnings) =
+            MempoolSerde::from_reader_lenient(&bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(mempool.txs, full.txs);
+        assert!(matches!(warnings[..], [DecodeWarning::SkippedTail(_)]));
+
+        let (mempool, warnings) =
+            MempoolSerde::from_reader_lenient(&bytes[..bytes.len() / 2]).unwrap();
+        let decoded = mempool.txs.len();
+        assert!(decoded > 0 && decoded < full.txs.len());
+        assert_eq!(mempool.txs, full.txs[..decoded]);
+        assert!(mempool.map_deltas.is_empty() && mempool.unbroadcast_txids.is_empty());
+        assert!(matches!(
+            &warnings[..],
+            [
+                DecodeWarning::SkippedTransactions { index, skipped, .. },
+                DecodeWarning::SkippedTail(reason),
+            ] if *index == decoded as u64
+                && *skipped == (full.txs.len() - decoded) as u64
+                && reason.contains("follow the skipped transactions")
+        ));
+
+        assert!(MempoolSerde::from_reader_lenient(&bytes[..4]).is_err());
+    }
+
+    #[test]
+    fn spending_script_finds_every_payment() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let script = mempool.txs[3].tx.output[0].script_pubkey.clone();
+        let found = mempool.spending_script(&script);
+        assert!(found.contains(&mempool.txs[3].txid()));
+        for txid in &found {
+            let txn = mempool.get(txid).unwrap();
+            assert!(txn.tx.output.iter().any(|out| out.script_pubkey == script));
+        }
+        assert!(
+            mempool
+                .spending_script(&ScriptBuf::from_bytes(vec![0x6a, 0xff]))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn clear_deltas_zeroes_both_views() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        mempool.set_fee_delta(&mempool.txs[0].txid(), 1_000);
+        mempool
+            .map_deltas
+            .insert(Txid::from_byte_array([7; 32]), -500);
+        mempool.txs[1].fee_delta = 250;
+
+        assert_eq!(mempool.clear_deltas(), 3);
+        assert!(mempool.map_deltas.is_empty());
+        assert!(mempool.txs.iter().all(|txn| txn.fee_delta == 0));
+        assert_eq!(mempool.clear_deltas(), 0);
+    }
+
+    #[test]
+    fn display_summarizes_without_transactions() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let txid = mempool.txs[0].txid();
+        mempool.set_fee_delta(&txid, 1_000);
+        mempool.unbroadcast_txids.insert(txid);
+        // Deltas for transactions not in the dump are counted and summed too.
+        mempool
+            .map_deltas
+            .insert(Txid::from_byte_array([7; 32]), -400);
+
+        let shown = mempool.to_string();
+        let lines: Vec<&str> = shown.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "Mempool dump version 2 (Bitcoin Core >=28.0 (v2, XOR key))".to_string(),
+                format!(
+                    "Transactions: {} ({} vB, {} WU)",
+                    mempool.txs.len(),
+                    mempool.total_vsize(),
+                    mempool.total_weight().to_wu()
+                ),
+                "Fee deltas:   2 (total 600 sat)".to_string(),
+                "Unbroadcast:  1".to_string(),
+            ]
+        );
+        assert!(!shown.contains(&txid.to_string()));
+    }
+
+    #[test]
+    fn xor_key_is_read_from_v2_dumps_only() {
+        let bytes = std::fs::read("./test/mempool_t4_v2_001.dat").unwrap();
+        let v2 = MempoolSerde::from_bytes(&bytes).unwrap();
+        // The key follows the version and its length prefix.
+        assert_eq!(v2.xor_key().map(|key| &key[..]), Some(&bytes[9..17]));
+
+        let mut v1 = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        assert_eq!(v1.xor_key(), None);
+        v1.xor_key = Some([1; XOR_KEY_SIZE]);
+        assert_eq!(v1.xor_key(), None);
+    }
+
+    #[test]
+    fn set_version_checks_the_key() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let key = [7; XOR_KEY_SIZE];
+
+        assert!(matches!(
+            mempool.set_version(MEMPOOL_DUMP_VERSION, None),
+            Err(MempoolSerdeError::XorKeyMismatch(MEMPOOL_DUMP_VERSION))
+        ));
+        assert!(matches!(
+            mempool.set_version(MEMPOOL_DUMP_VERSION_NO_XOR_KEY, Some(key)),
+            Err(MempoolSerdeError::XorKeyMismatch(
+                MEMPOOL_DUMP_VERSION_NO_XOR_KEY
+            ))
+        ));
+        assert!(matches!(
+            mempool.set_version(3, Some(key)),
+            Err(MempoolSerdeError::UnsupportedVersion(3))
+        ));
+        assert_eq!(mempool.version, MEMPOOL_DUMP_VERSION_NO_XOR_KEY);
+
+        mempool
+            .set_version(MEMPOOL_DUMP_VERSION, Some(key))
+            .unwrap();
+        assert_eq!(mempool.xor_key(), Some(&key));
+        mempool
+            .set_version(MEMPOOL_DUMP_VERSION_NO_XOR_KEY, None)
+            .unwrap();
+        assert_eq!(mempool.xor_key, None);
+    }
+
+    #[test]
+    fn version_conversion_roundtrips_byte_for_byte() {
+        let original = std::fs::read("./test/mempool_t4_v1_001.dat").unwrap();
+        let mut mempool = MempoolSerde::from_bytes(&original).unwrap();
+        mempool.upgrade_to_v2([0xa5; XOR_KEY_SIZE]);
+        let v2 = mempool.to_bytes().unwrap();
+
+        let mut mempool = MempoolSerde::from_bytes(&v2).unwrap();
+        mempool.downgrade_to_v1();
+        assert_eq!(mempool.core_compatibility(), CoreCompat::PreV28);
+        assert_eq!(mempool.to_bytes().unwrap(), original);
+    }
+
+    #[test]
+    fn rbf_signaling_follows_sequence_numbers() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        for txn in &mut mempool.txs {
+            for txin in &mut txn.tx_mut().input {
+                txin.sequence = bitcoin::Sequence::MAX;
+            }
+        }
+        assert!(mempool.rbf_signaling_txids().is_empty());
+
+        // 0xfffffffe opts out of RBF but enables locktime; only lower values signal.
+        mempool.txs[0].tx_mut().input[0].sequence = bitcoin::Sequence::ENABLE_LOCKTIME_NO_RBF;
+        assert!(!is_rbf_signaling(&mempool.txs[0].tx));
+
+        let last = mempool.txs.len() - 1;
+        mempool.txs[last].tx_mut().input[0].sequence = bitcoin::Sequence::ENABLE_RBF_NO_LOCKTIME;
+        assert!(is_rbf_signaling(&mempool.txs[last].tx));
+        assert_eq!(mempool.rbf_signaling_txids(), [mempool.txs[last].txid()]);
+    }
+
+    #[test]
+    fn write_to_roundtrips_through_from_reader() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let mut buf = Vec::new();
+        mempool.write_to(&mut buf).unwrap();
+        assert_eq!(buf, mempool.to_bytes().unwrap());
+        assert_eq!(MempoolSerde::from_reader(buf.as_slice()).unwrap(), mempool);
+    }
+
+    #[test]
+    fn txn_txid_is_memoized_until_mutated() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let mut txn = mempool.txs[0].clone();
+        let txid = txn.tx.compute_txid();
+        assert_eq!(txn.txid(), txid);
+        assert_eq!(txn.txid(), txid);
+
+        // Equality ignores whether the TXID was memoized.
+        let fresh = Txn::new(txn.tx.clone(), txn.time, txn.fee_delta);
+        assert_eq!(fresh, txn);
+
+        txn.tx_mut().lock_time = bitcoin::absolute::LockTime::from_consensus(1);
+        assert_ne!(txn.txid(), txid);
+        assert_eq!(txn.txid(), txn.tx.compute_txid());
+        assert_ne!(fresh, txn);
+    }
+
+    #[test]
+    fn basic_sanity_rejects_impossible_transactions() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let tx = mempool.txs[0].tx.clone();
+        assert_eq!(basic_sanity(&tx), Ok(()));
+
+        let mut no_inputs = tx.clone();
+        no_inputs.input.clear();
+        assert_eq!(basic_sanity(&no_inputs), Err(SanityError::NoInputs));
+
+        let mut no_outputs = tx.clone();
+        no_outputs.output.clear();
+        assert_eq!(basic_sanity(&no_outputs), Err(SanityError::NoOutputs));
+
+        let mut coinbase = tx.clone();
+        coinbase.input.truncate(1);
+        coinbase.input[0].previous_output = OutPoint::null();
+        assert_eq!(basic_sanity(&coinbase), Err(SanityError::Coinbase));
+
+        let mut null_prevout = tx.clone();
+        null_prevout.input.push(coinbase.input[0].clone());
+        let vin = null_prevout.input.len() - 1;
+        assert_eq!(
+            basic_sanity(&null_prevout),
+            Err(SanityError::NullPrevout(vin))
+        );
+
+        let mut version = tx;
+        version.version = bitcoin::transaction::Version(0);
+        assert_eq!(basic_sanity(&version), Err(SanityError::InvalidVersion(0)));
+
+        let mut empty = MempoolSerde::empty(MEMPOOL_DUMP_VERSION);
+        assert!(matches!(
+            empty.add_transaction(coinbase, 0, 0),
+            Err(MempoolSerdeError::Sanity(SanityError::Coinbase))
+        ));
+        assert!(empty.txs.is_empty());
+    }
+
+    #[test]
+    fn remove_transaction_clears_related_state() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txid = mempool.txs[0].tx.compute_txid();
+        let len = mempool.txs.len();
+        mempool.map_deltas.insert(txid, 500);
+        mempool.unbroadcast_txids.insert(txid);
+
+        let removed = mempool.remove_transaction(&txid).unwrap();
+        assert_eq!(removed.tx.compute_txid(), txid);
+        assert_eq!(mempool.txs.len(), len - 1);
+        assert!(!mempool.map_deltas.contains_key(&txid));
+        assert!(!mempool.unbroadcast_txids.contains(&txid));
+        assert!(mempool.remove_transaction(&txid).is_none());
+    }
+
+    #[test]
+    fn validate_reports_dangling_entries() {
+        use bitcoin::hashes::Hash;
+
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        assert!(mempool.validate().is_ok());
+
+        let unknown = Txid::all_zeros();
+        mempool.map_deltas.insert(unknown, 100);
+        mempool.unbroadcast_txids.insert(unknown);
+
+        let issues = mempool.validate().unwrap_err();
+        assert_eq!(issues.len(), 2);
+        assert!(issues.contains(&ValidationIssue::DanglingDelta(unknown)));
+        assert!(issues.contains(&ValidationIssue::UnknownUnbroadcast(unknown)));
+    }
+
+    #[test]
+    fn stream_stops_on_break() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+
+        let mut seen = 0;
+        MempoolSerde::stream(Path::new("./test/mempool_t4_v2_001.dat"), |txn| {
+            assert_eq!(txn.tx, mempool.txs[seen].tx);
+            seen += 1;
+            if seen == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+        assert_eq!(seen, 2.min(mempool.txs.len()));
+    }
+
+    #[test]
+    fn from_bytes_matches_file() {
+        let bytes = std::fs::read("./test/mempool_t4_v2_001.dat").unwrap();
+        let from_bytes = MempoolSerde::from_bytes(&bytes).unwrap();
+        assert_eq!(from_bytes.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn write_to_file_replaces_atomically() {
+        let dir = std::env::temp_dir().join(format!("windfish-write-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mempool.dat");
+        std::fs::write(&path, b"stale").unwrap();
+
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        mempool.write_to_file(&path).unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), mempool.to_bytes().unwrap());
+        assert!(!dir.join("mempool.dat.tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unsupported_version_is_an_error() {
+        let mut bytes = std::fs::read("./test/mempool_t4_v1_001.dat").unwrap();
+        bytes[0] = 3;
+        assert!(matches!(
+            MempoolSerde::from_bytes(&bytes),
+            Err(MempoolSerdeError::UnsupportedVersion(3))
+        ));
+    }
+
+    #[test]
+    fn effective_feerate_needs_prevouts() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txn = &mempool.txs[0];
+        assert_eq!(txn.vsize(), txn.tx.vsize());
+        assert!(txn.effective_feerate(&HashMap::new()).is_none());
+
+        let output: u64 = txn.tx.output.iter().map(|out| out.value.to_sat()).sum();
+        let fee = 1_000 * txn.vsize() as u64;
+        let prevouts: HashMap<OutPoint, Amount> = txn
+            .tx
+            .input
+            .iter()
+            .enumerate()
+            .map(|(i, txin)| {
+                let value = if i == 0 { output + fee } else { 0 };
+                (txin.previous_output, Amount::from_sat(value))
+            })
+            .collect();
+
+        let feerate = txn.effective_feerate(&prevouts).unwrap();
+        assert_eq!(feerate.to_sat_per_kwu(), fee * 1_000 / txn.weight().to_wu());
+        assert!(feerate.to_sat_per_vb_floor() >= 1_000);
+    }
+
+    #[test]
+    fn set_fee_delta_syncs_map_deltas() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txid = mempool.txs[0].tx.compute_txid();
+        let original = mempool.txs[0].fee_delta;
+
+        assert_eq!(mempool.set_fee_delta(&txid, 2_500), Some(original));
+        assert_eq!(mempool.txs[0].fee_delta, 2_500);
+        assert_eq!(mempool.map_deltas.get(&txid), Some(&2_500));
+
+        assert_eq!(mempool.set_fee_delta(&txid, 0), Some(2_500));
+        assert!(!mempool.map_deltas.contains_key(&txid));
+    }
+
+    #[test]
+    fn prioritise_records_absent_txids() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txid = mempool.txs[0].tx.compute_txid();
+        let absent = mempool.txs.remove(1).tx.compute_txid();
+
+        mempool.prioritise(&txid, 700);
+        assert_eq!(mempool.txs[0].fee_delta, 700);
+        assert_eq!(mempool.map_deltas.get(&txid), Some(&700));
+
+        mempool.prioritise(&absent, -300);
+        assert_eq!(mempool.map_deltas.get(&absent), Some(&-300));
+
+        mempool.prioritise(&txid, 0);
+        mempool.prioritise(&absent, 0);
+        assert_eq!(mempool.txs[0].fee_delta, 0);
+        assert!(mempool.map_deltas.is_empty());
+    }
+
+    #[test]
+    fn merge_applies_policy() {
+        let mut v1 = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let mut v2 = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let len = v1.txs.len();
+        let removed = v1.txs.remove(0);
+        v2.txs[1].time = 42;
+
+        let report = v1.merge(v2, MergePolicy::KeepExisting);
+        assert_eq!(
+            report,
+            MergeReport {
+                added: 1,
+                skipped: len - 1,
+                overwritten: 0,
+            }
+        );
+        assert_eq!(v1.txs.len(), len);
+        assert_eq!(v1.txs.last().unwrap().tx, removed.tx);
+        assert_ne!(v1.txs[0].time, 42);
+        assert_eq!(v1.version, MEMPOOL_DUMP_VERSION);
+        assert!(v1.xor_key.is_some());
+
+        let mut v2 = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        v2.txs[1].time = 42;
+        let report = v1.merge(v2, MergePolicy::PreferOther);
+        assert_eq!(report.overwritten, len);
+        assert_eq!(v1.txs[0].time, 42);
+    }
+
+    #[test]
+    fn repair_drops_dangling_and_rederives() {
+        use bitcoin::hashes::Hash;
+
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let unknown = Txid::all_zeros();
+        mempool.map_deltas.insert(unknown, 100);
+        mempool.unbroadcast_txids.insert(unknown);
+        mempool.txs[0].fee_delta = 700;
+        let txid = mempool.txs[0].tx.compute_txid();
+
+        let report = mempool.repair(false, false);
+        assert_eq!(report.removed_deltas, vec![unknown]);
+        assert_eq!(report.removed_unbroadcast, vec![unknown]);
+        assert!(report.added_deltas.is_empty());
+        assert!(mempool.validate().is_ok());
+
+        let report = mempool.repair(true, false);
+        assert_eq!(report.added_deltas, vec![(txid, 700)]);
+        assert_eq!(mempool.map_deltas.get(&txid), Some(&700));
+    }
+
+    #[test]
+    fn duplicates_are_reported_and_repaired() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        assert!(mempool.duplicates().is_empty());
+        let len = mempool.txs.len();
+        let (first, second) = (mempool.txs[1].clone(), mempool.txs[4].clone());
+        mempool
+            .txs
+            .extend([second.clone(), first.clone(), second.clone()]);
+
+        assert_eq!(
+            mempool.duplicates(),
+            vec![
+                (first.txid(), vec![1, len + 1]),
+                (second.txid(), vec![4, len, len + 2]),
+            ]
+        );
+        let issues = mempool.validate().unwrap_err();
+        assert!(issues.contains(&ValidationIssue::DuplicateTransaction(first.txid())));
+        assert!(issues.contains(&ValidationIssue::DuplicateTransaction(second.txid())));
+
+        assert!(mempool.repair(false, false).removed_duplicates.is_empty());
+        assert_eq!(mempool.txs.len(), len + 3);
+        let report = mempool.repair(false, true);
+        assert_eq!(
+            report.removed_duplicates,
+            vec![second.txid(), first.txid(), second.txid()]
+        );
+        assert_eq!(mempool.txs.len(), len);
+        assert_eq!(mempool.txs[1], first);
+        assert!(mempool.validate().is_ok());
+    }
+
+    #[test]
+    fn sorting_changes_the_written_order() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        for (i, txn) in mempool.txs.iter_mut().enumerate() {
+            txn.time = 1_700_000_000 - i64::try_from(i % 7).unwrap();
+            txn.fee_delta = i64::try_from(i % 4).unwrap();
+        }
+
+        mempool.sort_by_time();
+        assert!(mempool.txs.is_sorted_by_key(|txn| txn.time));
+        let reloaded = MempoolSerde::from_bytes(&mempool.to_bytes().unwrap()).unwrap();
+        assert_eq!(reloaded.txs, mempool.txs);
+
+        mempool.sort_by_fee_delta();
+        assert!(mempool.txs.is_sorted_by_key(|txn| Reverse(txn.fee_delta)));
+        // Stable, so equal fee deltas stay in time order.
+        assert!(
+            mempool
+                .txs
+                .chunk_by(|a, b| a.fee_delta == b.fee_delta)
+                .all(|run| run.is_sorted_by_key(|txn| txn.time))
+        );
+
+        mempool.sort_by(|a, b| a.vsize().cmp(&b.vsize()));
+        assert!(mempool.txs.is_sorted_by_key(Txn::vsize));
+    }
+
+    #[test]
+    fn dedup_keeps_the_first_copy() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let original = mempool.clone();
+        assert_eq!(mempool.dedup(), 0);
+        assert_eq!(mempool, original);
+
+        let txid = mempool.txs[2].txid();
+        mempool.set_fee_delta(&txid, 500);
+        mempool.unbroadcast_txids.insert(txid);
+        let mut copy = mempool.txs[2].clone();
+        copy.time += 60;
+        mempool.txs.insert(5, copy.clone());
+        mempool.txs.push(copy);
+
+        assert_eq!(mempool.dedup(), 2);
+        assert_eq!(mempool.txs.len(), original.txs.len());
+        assert_eq!(mempool.txs[2].time, original.txs[2].time);
+        assert_eq!(mempool.map_deltas.get(&txid), Some(&500));
+        assert!(mempool.validate().is_ok());
+    }
+
+    #[test]
+    fn retain_by_feerate_handles_unknown_prevouts() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let len = mempool.txs.len();
+        let txid = mempool.txs[0].tx.compute_txid();
+        mempool.unbroadcast_txids.insert(txid);
+
+        let min = FeeRate::from_sat_per_vb_unchecked(1);
+        assert_eq!(mempool.retain_by_feerate(min, &HashMap::new(), true), 0);
+        assert_eq!(mempool.retain_by_feerate(min, &HashMap::new(), false), len);
+        assert!(mempool.txs.is_empty());
+        assert!(mempool.unbroadcast_txids.is_empty());
+        assert!(mempool.validate().is_ok());
+    }
+
+    #[test]
+    fn retain_by_age_expires_like_core() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        for (time, txn) in (0..).zip(&mut mempool.txs) {
+            txn.time = time;
+        }
+        let len = mempool.txs.len();
+        let oldest = mempool.txs[0].tx.compute_txid();
+        mempool.set_fee_delta(&oldest, 1_000);
+        mempool.unbroadcast_txids.insert(oldest);
+
+        // Times are 0..len, so a cutoff of 1 expires entries 0 and 1.
+        assert_eq!(mempool.retain_by_age(11, 10), 2);
+        assert_eq!(mempool.txs.len(), len - 2);
+        assert!(!mempool.map_deltas.contains_key(&oldest));
+        assert!(mempool.unbroadcast_txids.is_empty());
+        assert!(mempool.validate().is_ok());
+
+        // Extreme arguments saturate rather than overflow.
+        assert_eq!(mempool.retain_by_age(i64::MIN, i64::MAX), 0);
+        assert_eq!(
+            mempool.retain_by_age(
+                DEFAULT_MEMPOOL_EXPIRY_SECS + 100,
+                DEFAULT_MEMPOOL_EXPIRY_SECS
+            ),
+            len - 2
+        );
+    }
+
+    #[test]
+    fn extremes_prefer_first_in_file_order() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        for txn in &mut mempool.txs {
+            txn.time = 100;
+            txn.fee_delta = 0;
+        }
+        mempool.txs[1].time = 50;
+        mempool.txs[2].time = 50;
+        mempool.txs[3].time = 200;
+        mempool.txs[4].time = 200;
+        mempool.txs[5].fee_delta = 7;
+        mempool.txs[6].fee_delta = 7;
+
+        assert_eq!(mempool.oldest(), Some(&mempool.txs[1]));
+        assert_eq!(mempool.newest(), Some(&mempool.txs[3]));
+        assert_eq!(mempool.highest_fee_delta(), Some(&mempool.txs[5]));
+
+        let empty = MempoolSerde::empty(MEMPOOL_DUMP_VERSION);
+        assert!(empty.oldest().is_none());
+        assert!(empty.newest().is_none());
+        assert!(empty.highest_fee_delta().is_none());
+    }
+
+    #[test]
+    fn peek_count_matches_decoded_len() {
+        for path in [
+            "./test/mempool_t4_v1_001.dat",
+            "./test/mempool_t4_v2_001.dat",
+        ] {
+            let mempool = MempoolSerde::new(Path::new(path)).unwrap();
+            let count = MempoolSerde::peek_count(Path::new(path)).unwrap();
+            assert_eq!(count, mempool.txs.len() as u64);
+        }
+    }
+
+    #[test]
+    fn get_finds_by_txid() {
+        use bitcoin::hashes::Hash;
+
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txid = mempool.txs[3].tx.compute_txid();
+
+        assert!(mempool.contains_txid(&txid));
+        assert_eq!(mempool.get(&txid).unwrap().tx, mempool.txs[3].tx);
+        assert!(!mempool.contains_txid(&Txid::all_zeros()));
+        assert!(mempool.get(&Txid::all_zeros()).is_none());
+    }
+
+    #[test]
+    fn encoding_is_deterministic() {
+        let mut a = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let mut b = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txids: Vec<Txid> = a.iter_with_txid().map(|(txid, _)| txid).collect();
+
+        for (delta, txid) in (0i64..).zip(&txids) {
+            a.map_deltas.insert(*txid, delta);
+            a.unbroadcast_txids.insert(*txid);
+        }
+        for (delta, txid) in (0i64..).zip(&txids).collect::<Vec<_>>().into_iter().rev() {
+            b.map_deltas.insert(*txid, delta);
+            b.unbroadcast_txids.insert(*txid);
+        }
+
+        assert_eq!(a.to_bytes().unwrap(), b.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn roundtrip_preserves_unsorted_order() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txids: Vec<Txid> = mempool
+            .iter_with_txid()
+            .map(|(txid, _)| txid)
+            .take(3)
+            .collect();
+        for (delta, txid) in (1i64..).zip(&txids) {
+            mempool.map_deltas.insert(*txid, delta);
+            mempool.unbroadcast_txids.insert(*txid);
+        }
+        let sorted = mempool.to_bytes().unwrap();
+
+        // Reverse the three 40-byte delta and 32-byte unbroadcast entries
+        // that close the file to get a dump in non-sorted order.
+        let mut bytes = sorted.clone();
+        let unbroadcast_start = bytes.len() - 3 * 32;
+        let deltas_start = unbroadcast_start - 1 - 3 * 40;
+        let deltas: Vec<Vec<u8>> = sorted[deltas_start..unbroadcast_start - 1]
+            .chunks(40)
+            .rev()
+            .map(<[u8]>::to_vec)
+            .collect();
+        let unbroadcast: Vec<Vec<u8>> = sorted[unbroadcast_start..]
+            .chunks(32)
+            .rev()
+            .map(<[u8]>::to_vec)
+            .collect();
+        bytes[deltas_start..unbroadcast_start - 1].copy_from_slice(&deltas.concat());
+        bytes[unbroadcast_start..].copy_from_slice(&unbroadcast.concat());
+        assert_ne!(bytes, sorted);
+
+        let reread = MempoolSerde::from_bytes(&bytes).unwrap();
+        assert_eq!(reread.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn absurd_tx_count_is_corrupt() {
+        let mut bytes = std::fs::read("./test/mempool_t4_v1_001.dat").unwrap();
+        bytes[8..16].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        assert!(matches!(
+            MempoolSerde::from_bytes(&bytes),
+            Err(MempoolSerdeError::CorruptFile(_))
+        ));
+
+        // A count within the ceiling but beyond what the bytes could hold.
+        bytes[8..16].copy_from_slice(&1000u64.to_le_bytes());
+        assert!(matches!(
+            MempoolSerde::from_bytes(&bytes),
+            Err(MempoolSerdeError::CorruptFile(_))
+        ));
+
+        let bytes = std::fs::read("./test/mempool_t4_v1_001.dat").unwrap();
+        assert!(matches!(
+            MempoolSerde::from_reader_with_max_tx_count(bytes.as_slice(), 10),
+            Err(MempoolSerdeError::CorruptFile(_))
+        ));
+    }
+
+    #[test]
+    fn progress_reports_each_transaction() {
+        let bytes = std::fs::read("./test/mempool_t4_v2_001.dat").unwrap();
+        let mut calls = Vec::new();
+        let mempool =
+            MempoolSerde::from_reader_with_progress(bytes.as_slice(), |decoded, total| {
+                calls.push((decoded, total));
+            })
+            .unwrap();
+
+        let total = mempool.txs.len() as u64;
+        assert_eq!(calls, (1..=total).map(|i| (i, total)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn totals_sum_every_transaction() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let vsize: usize = mempool.txs.iter().map(|txn| txn.tx.vsize()).sum();
+        let weight: u64 = mempool.txs.iter().map(|txn| txn.tx.weight().to_wu()).sum();
+
+        assert_eq!(mempool.total_vsize(), vsize);
+        assert_eq!(mempool.total_weight().to_wu(), weight);
+    }
+
+    #[test]
+    fn total_output_value_checks_for_overflow() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let sats: u64 = mempool
+            .txs
+            .iter()
+            .flat_map(|txn| &txn.tx.output)
+            .map(|out| out.value.to_sat())
+            .sum();
+        assert_eq!(mempool.total_output_value().unwrap().to_sat(), sats);
+        assert_eq!(
+            MempoolSerde::empty(MEMPOOL_DUMP_VERSION)
+                .total_output_value()
+                .unwrap(),
+            Amount::ZERO
+        );
+
+        mempool.txs[0].tx_mut().output[0].value = Amount::MAX;
+        assert!(matches!(
+            mempool.total_output_value(),
+            Err(MempoolSerdeError::ValueOverflow)
+        ));
+    }
+
+    #[test]
+    fn empty_builds_a_loadable_dump() {
+        let source = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let mempool = MempoolSerde::empty(MEMPOOL_DUMP_VERSION_NO_XOR_KEY)
+            .with_transaction(source.txs[0].tx.clone(), 1_700_000_000, 0)
+            .unwrap()
+            .with_transaction(source.txs[1].tx.clone(), 1_700_000_001, 500)
+            .unwrap();
+
+        let reread = MempoolSerde::from_bytes(&mempool.to_bytes().unwrap()).unwrap();
+        assert_eq!(reread.txs.len(), 2);
+        assert_eq!(reread.txs[1].time, 1_700_000_001);
+        assert_eq!(reread.map_deltas.len(), 1);
+        assert!(
+            MempoolSerde::empty(MEMPOOL_DUMP_VERSION)
+                .to_bytes()
+                .is_ok_and(|bytes| MempoolSerde::from_bytes(&bytes).is_ok())
+        );
+    }
+
+    #[test]
+    fn to_bytes_stripped_drops_witnesses() {
+        let mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        assert!(
+            mempool
+                .txs
+                .iter()
+                .any(|txn| txn.tx.input.iter().any(|txin| !txin.witness.is_empty()))
+        );
+
+        let stripped = MempoolSerde::from_bytes(&mempool.to_bytes_stripped().unwrap()).unwrap();
+        assert_eq!(stripped.txs.len(), mempool.txs.len());
+        for (stripped, txn) in stripped.txs.iter().zip(&mempool.txs) {
+            assert!(stripped.tx.input.iter().all(|txin| txin.witness.is_empty()));
+            assert_eq!(stripped.tx.compute_txid(), txn.tx.compute_txid());
+            assert_eq!(stripped.time, txn.time);
+        }
+    }
+
+    #[test]
+    fn fee_delta_histogram_buckets_by_lower_bound() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let len = mempool.txs.len();
+        mempool.txs[0].fee_delta = 1_500;
+        mempool.txs[1].fee_delta = 1_999;
+        mempool.txs[2].fee_delta = -1;
+
+        let histogram = mempool.fee_delta_histogram(1_000);
+        assert_eq!(histogram.get(&1_000), Some(&2));
+        assert_eq!(histogram.get(&-1_000), Some(&1));
+        assert_eq!(histogram.get(&0), Some(&(len - 3)));
+    }
+
+    #[test]
+    fn anomalies_respect_thresholds() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txid = mempool.txs[0].tx.compute_txid();
+        let outputs = mempool.txs[0].tx.output.len();
+        let strict = AnomalyConfig {
+            max_outputs: 0,
+            ..AnomalyConfig::default()
+        };
+        assert!(
+            mempool
+                .anomalies(&strict)
+                .contains(&(txid, Anomaly::TooManyOutputs(outputs)))
+        );
+
+        mempool.txs[0].tx_mut().output[0].value = Amount::from_sat(100);
+        let txid = mempool.txs[0].tx.compute_txid();
+        assert!(
+            mempool
+                .anomalies(&AnomalyConfig::default())
+                .contains(&(txid, Anomaly::DustOutput(0, Amount::from_sat(100))))
+        );
+
+        mempool.txs[0].tx_mut().output[0].value = Amount::ZERO;
+        let txid = mempool.txs[0].tx.compute_txid();
+        assert!(
+            mempool
+                .anomalies(&AnomalyConfig::default())
+                .contains(&(txid, Anomaly::ZeroValueOutput(0)))
+        );
+    }
+
+    #[test]
+    fn anomalies_flag_overflowing_output_totals() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let tx = mempool.txs[0].tx_mut();
+        tx.output.truncate(1);
+        tx.output[0].value = Amount::from_sat(u64::MAX / 2 + 1);
+        tx.output.push(tx.output[0].clone());
+        assert!(tx.output[0].value > Amount::MAX_MONEY);
+        let txid = mempool.txs[0].tx.compute_txid();
+
+        let lenient = AnomalyConfig {
+            max_total_output: Amount::MAX,
+            ..AnomalyConfig::default()
+        };
+        assert!(
+            mempool
+                .anomalies(&lenient)
+                .contains(&(txid, Anomaly::LargeOutputTotal(Amount::MAX)))
+        );
+    }
+
+    #[test]
+    fn append_transactions_streams_into_place() {
+        let dir = std::env::temp_dir().join(format!("windfish-append-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mempool.dat");
+
+        let mut full = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let first = full.txs[0].tx.compute_txid();
+        let last = full.txs[full.txs.len() - 1].tx.compute_txid();
+        full.set_fee_delta(&first, 100);
+        full.unbroadcast_txids.insert(first);
+
+        let mut trimmed = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        trimmed.set_fee_delta(&first, 100);
+        trimmed.unbroadcast_txids.insert(first);
+        let mut appended = trimmed.txs.split_off(trimmed.txs.len() - 2);
+        appended[1].fee_delta = 300;
+        full.set_fee_delta(&last, 300);
+        trimmed.write_to_file(&path).unwrap();
+
+        MempoolSerde::append_transactions(&path, &appended).unwrap();
+        assert_eq!(MempoolSerde::new(&path).unwrap(), full);
+
+        assert!(matches!(
+            MempoolSerde::append_transactions(&path, &appended[..1]),
+            Err(MempoolSerdeError::DuplicateTxid(_))
+        ));
+        assert!(!dir.join("mempool.dat.tmp").exists());
+
+        let mut insane = appended[0].clone();
+        insane.tx_mut().input.clear();
+        assert!(matches!(
+            MempoolSerde::append_transactions(&path, &[insane]),
+            Err(MempoolSerdeError::Sanity(SanityError::NoInputs))
+        ));
+
+        let mut crafted = 1u64.to_le_bytes().to_vec();
+        crafted.extend(u64::MAX.to_le_bytes());
+        std::fs::write(&path, crafted).unwrap();
+        assert!(matches!(
+            MempoolSerde::append_transactions(&path, &appended),
+            Err(MempoolSerdeError::CorruptFile(_))
+        ));
+        assert!(!dir.join("mempool.dat.tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn equality_ignores_decode_order() {
+        let mut mempool = MempoolSerde::new(Path::new("./test/mempool_t4_v1_001.dat")).unwrap();
+        let txids: Vec<Txid> = mempool
+            .iter_with_txid()
+            .map(|(txid, _)| txid)
+            .take(2)
+            .collect();
+        for txid in &txids {
+            mempool.unbroadcast_txids.insert(*txid);
+        }
+
+        // The reread copy has recorded a decode order; the original has not.
+        let reread = MempoolSerde::from_bytes(&mempool.to_bytes().unwrap()).unwrap();
+        assert_eq!(reread, mempool);
+
+        let mut copy = reread.clone();
+        assert_eq!(copy, reread);
+        copy.txs[0].time += 1;
+        assert_ne!(copy, reread);
+    }
+
+    use bitcoin::{ScriptBuf, Sequence, TxIn, TxOut, Witness, absolute, hashes::Hash, transaction};
+    use proptest::{
+        collection::{hash_map, hash_set, vec},
+        prelude::*,
+    };
+
+    fn arb_txid() -> impl Strategy<Value = Txid> {
+        any::<[u8; 32]>().prop_map(Txid::from_byte_array)
+    }
+
+    // At least one input, as an empty input list reads as the segwit marker.
+    fn arb_tx() -> impl Strategy<Value = Transaction> {
+        let input = (
+            arb_txid(),
+            any::<u32>(),
+            vec(any::<u8>(), 0..40),
+            any::<u32>(),
+            vec(vec(any::<u8>(), 0..40), 0..3),
+        )
+            .prop_map(|(txid, vout, script_sig, sequence, witness)| TxIn {
+                previous_output: OutPoint::new(txid, vout),
+                script_sig: ScriptBuf::from_bytes(script_sig),
+                sequence: Sequence(sequence),
+                witness: Witness::from_slice(&witness),
+            });
+        let output = (0..=Amount::MAX_MONEY.to_sat(), vec(any::<u8>(), 0..40)).prop_map(
+            |(value, script_pubkey)| TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: ScriptBuf::from_bytes(script_pubkey),
+            },
+        );
+        (
+            any::<i32>(),
+            vec(input, 1..4),
+            vec(output, 0..4),
+            any::<u32>(),
+        )
+            .prop_map(|(version, input, output, lock_time)| Transaction {
+                version: transaction::Version(version),
+                lock_time: absolute::LockTime::from_consensus(lock_time),
+                input,
+                output,
+            })
+    }
+
+    // V2 dumps always carry a key once written, so only they get one.
+    fn arb_mempool() -> impl Strategy<Value = MempoolSerde> {
+        (
+            prop::option::of(any::<[u8; XOR_KEY_SIZE]>()),
+            vec((arb_tx(), any::<i64>(), any::<i64>()), 0..8),
+            hash_map(arb_txid(), any::<i64>(), 0..8),
+            hash_set(arb_txid(), 0..8),
+        )
+            .prop_map(|(xor_key, txs, map_deltas, unbroadcast_txids)| {
+                let version = if xor_key.is_some() {
+                    MEMPOOL_DUMP_VERSION
+                } else {
+                    MEMPOOL_DUMP_VERSION_NO_XOR_KEY
+                };
+                MempoolSerde {
+                    xor_key,
+                    txs: txs
+                        .into_iter()
+                        .map(|(tx, time, fee_delta)| Txn::new(tx, time, fee_delta))
+                        .collect(),
+                    map_deltas,
+                    unbroadcast_txids,
+                    ..MempoolSerde::empty(version)
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn roundtrip_arbitrary_mempools(mempool in arb_mempool()) {
+            let bytes = mempool.to_bytes().unwrap();
+            let decoded = MempoolSerde::from_bytes(&bytes).unwrap();
+            prop_assert_eq!(&decoded, &mempool);
+            prop_assert_eq!(decoded.to_bytes().unwrap(), bytes);
+        }
     }
 }
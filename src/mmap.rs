@@ -0,0 +1,53 @@
+//! Decoding dumps straight from a memory-mapped file.
+
+use crate::{MempoolResult, MempoolSerde, compress::Compression};
+use memmap2::Mmap;
+use std::{fs::File, path::Path};
+
+impl MempoolSerde {
+    /// Decodes the mempool.dat file at `path` like [`Self::new`], but from a
+    /// read-only memory map rather than through a buffered reader, so the
+    /// file is paged in by the OS instead of copied.
+    ///
+    /// Compressed files gain nothing from mapping and are read as in
+    /// [`Self::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or mapped, or any error
+    /// [`Self::from_bytes`] can return.
+    pub fn from_mmap(path: &Path) -> MempoolResult<Self> {
+        if Compression::from_path(path) != Compression::None {
+            return Self::new(path);
+        }
+
+        let file = File::open(path)?;
+        // SAFETY: the map is only read while decoding and dropped before
+        // returning. Another process truncating or rewriting the file in the
+        // meantime is undefined behavior, as with any mapped file; Bitcoin
+        // Core replaces mempool.dat by renaming a new file over it, which
+        // leaves this mapping intact.
+        let map = unsafe { Mmap::map(&file)? };
+        Self::from_bytes(&map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_mmap_matches_new() {
+        for path in [
+            "./test/mempool_t4_v1_001.dat",
+            "./test/mempool_t4_v2_001.dat",
+        ] {
+            let path = Path::new(path);
+            assert_eq!(
+                MempoolSerde::from_mmap(path).unwrap(),
+                MempoolSerde::new(path).unwrap()
+            );
+        }
+        assert!(MempoolSerde::from_mmap(Path::new("./test/missing.dat")).is_err());
+    }
+}
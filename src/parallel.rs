@@ -0,0 +1,213 @@
+//! Multi-threaded decoding of large dumps.
+
+use crate::{
+    DEFAULT_MAX_TX_COUNT, MempoolResult, MempoolSerde, MempoolSerdeError, Payload, Txn,
+    check_tx_count,
+};
+use bitcoin::consensus::ReadExt;
+use rayon::prelude::*;
+use std::{io::Read, ops::Range, sync::OnceLock};
+
+impl MempoolSerde {
+    /// Decodes an in-memory mempool.dat image like [`Self::from_bytes`], but
+    /// spreads the work over all cores.
+    ///
+    /// Entries can only be told apart by parsing them in order, so a first
+    /// pass walks the transactions' length prefixes to find where each entry
+    /// ends. The entries are then decoded and their TXIDs computed in
+    /// parallel, which is where the time goes; the TXIDs stay memoized for
+    /// [`Txn::txid`].
+    ///
+    /// # Errors
+    ///
+    /// Returns any error [`Self::from_bytes`] can return, though a corrupt
+    /// dump may be reported differently.
+    pub fn from_bytes_parallel(bytes: &[u8]) -> MempoolResult<Self> {
+        let Payload {
+            version,
+            xor_key,
+            header_len,
+            mut reader,
+        } = Payload::open(bytes)?;
+        let mut body = Vec::with_capacity(bytes.len());
+        reader.read_to_end(&mut body)?;
+        let mut f = body.as_slice();
+
+        let tx_count = f.read_u64()?;
+        check_tx_count(
+            tx_count,
+            DEFAULT_MAX_TX_COUNT,
+            Some((bytes.len() as u64).saturating_sub(header_len + 8)),
+        )?;
+
+        let mut entries: Vec<Range<usize>> = Vec::new();
+        let mut start = body.len() - f.len();
+        for _ in 0..tx_count {
+            let at = |err: MempoolSerdeError| err.at(entries.len(), header_len + start as u64);
+            // The transaction is followed by its entry time and fee delta.
+            let end = start + tx_len(&body[start..]).map_err(at)? + 16;
+            if end > body.len() {
+                return Err(at(truncated()));
+            }
+            entries.push(start..end);
+            start = end;
+        }
+
+        let txs = entries
+            .into_par_iter()
+            .enumerate()
+            .map(|(index, range)| {
+                let offset = header_len + range.start as u64;
+                let mut entry = &body[range];
+                let txn = Txn::decode(&mut entry).map_err(|err| err.at(index, offset))?;
+                if !entry.is_empty() {
+                    return Err(MempoolSerdeError::CorruptFile(
+                        "transaction length does not match its encoding".to_string(),
+                    )
+                    .at(index, offset));
+                }
+                Ok(Txn {
+                    txid: OnceLock::from(txn.tx.compute_txid()),
+                    ..txn
+                })
+            })
+            .collect::<MempoolResult<Vec<Txn>>>()?;
+
+        Self::decode_tail(&mut &body[start..], version, xor_key, txs)
+    }
+}
+
+fn truncated() -> MempoolSerdeError {
+    MempoolSerdeError::Io(std::io::ErrorKind::UnexpectedEof.into())
+}
+
+/// Returns the length of the serialized transaction at the start of `bytes`,
+/// following the layout `Transaction::consensus_decode` reads. Only lengths
+/// are checked; the transaction is decoded properly afterwards.
+fn tx_len(bytes: &[u8]) -> MempoolResult<usize> {
+    let mut scan = Scan { bytes, pos: 0 };
+
+    scan.skip(4)?; // version
+    let mut inputs = scan.compact_size()?;
+    let segwit = inputs == 0;
+    if segwit {
+        scan.skip(1)?; // flag
+        inputs = scan.compact_size()?;
+    }
+    for _ in 0..inputs {
+        scan.skip(36)?; // outpoint
+        let script_len = scan.compact_size()?;
+        scan.skip(script_len)?;
+        scan.skip(4)?; // sequence
+    }
+    for _ in 0..scan.compact_size()? {
+        scan.skip(8)?; // value
+        let script_len = scan.compact_size()?;
+        scan.skip(script_len)?;
+    }
+    if segwit {
+        for _ in 0..inputs {
+            for _ in 0..scan.compact_size()? {
+                let item_len = scan.compact_size()?;
+                scan.skip(item_len)?;
+            }
+        }
+    }
+    scan.skip(4)?; // lock time
+
+    Ok(scan.pos)
+}
+
+/// A bounds-checked cursor over `bytes`.
+struct Scan<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scan<'_> {
+    fn skip(&mut self, n: u64) -> MempoolResult<()> {
+        self.take(usize::try_from(n).map_err(|_| truncated())?)
+            .map(|_| ())
+    }
+
+    fn take(&mut self, n: usize) -> MempoolResult<&[u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(truncated)?;
+        let taken = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn compact_size(&mut self) -> MempoolResult<u64> {
+        let width = match self.take(1)?[0] {
+            0xfd => 2,
+            0xfe => 4,
+            0xff => 8,
+            n => return Ok(u64::from(n)),
+        };
+        let mut value = [0; 8];
+        value[..width].copy_from_slice(self.take(width)?);
+        Ok(u64::from_le_bytes(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MEMPOOL_DUMP_VERSION;
+    use bitcoin::absolute::LockTime;
+    use std::{path::Path, time::Instant};
+
+    #[test]
+    fn parallel_decode_matches_serial() {
+        for path in [
+            "./test/mempool_t4_v1_001.dat",
+            "./test/mempool_t4_v2_001.dat",
+        ] {
+            let bytes = std::fs::read(path).unwrap();
+            let serial = MempoolSerde::from_bytes(&bytes).unwrap();
+            let parallel = MempoolSerde::from_bytes_parallel(&bytes).unwrap();
+            assert_eq!(parallel, serial);
+            assert_eq!(parallel.to_bytes().unwrap(), bytes);
+
+            for cut in [bytes.len() / 2, bytes.len() - 1] {
+                assert!(MempoolSerde::from_bytes_parallel(&bytes[..cut]).is_err());
+            }
+        }
+    }
+
+    /// Compares the serial and parallel decoders on a large dump. Run with
+    /// `cargo test --release --features parallel -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_parallel_against_serial_decode() {
+        let template = MempoolSerde::new(Path::new("./test/mempool_t4_v2_001.dat")).unwrap();
+        let mut mempool = MempoolSerde::empty(MEMPOOL_DUMP_VERSION);
+        for i in 0..200_000 {
+            let mut tx = template.txs[i % template.txs.len()].tx.clone();
+            tx.lock_time = LockTime::from_consensus(u32::try_from(i).unwrap());
+            mempool.txs.push(Txn::new(tx, 0, 0));
+        }
+        let bytes = mempool.to_bytes().unwrap();
+
+        let start = Instant::now();
+        // The parallel decoder also computes every TXID, so the serial one
+        // must too for a fair comparison.
+        let serial = MempoolSerde::from_bytes(&bytes).unwrap();
+        let _: Vec<_> = serial.txs.iter().map(Txn::txid).collect();
+        let serial_time = start.elapsed();
+        let start = Instant::now();
+        let parallel = MempoolSerde::from_bytes_parallel(&bytes).unwrap();
+        let parallel_time = start.elapsed();
+
+        assert_eq!(parallel, serial);
+        eprintln!(
+            "{} bytes: serial {serial_time:?}, parallel {parallel_time:?} on {} threads",
+            bytes.len(),
+            rayon::current_num_threads()
+        );
+    }
+}
@@ -1,81 +1,714 @@
 #![allow(clippy::too_many_lines)]
 
-use bitcoin::{Transaction, consensus::Decodable};
-use clap::Parser;
+use bitcoin::{
+    Address, Amount, Network, ScriptBuf, Transaction, Txid,
+    consensus::{
+        Decodable,
+        encode::{serialize, serialize_hex},
+    },
+};
+use clap::{Parser, ValueEnum};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Position, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
 };
+use serde_json::{Number, Value, json};
+use session::Session;
 use std::{
-    io,
-    path::PathBuf,
+    collections::{HashMap, HashSet},
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
     time::{Duration, Instant},
 };
-use windfish::{MempoolSerde, Txn};
+use theme::{Theme, ThemeName};
+use windfish::{AnomalyConfig, CoreCompat, MempoolSerde, Txn, XOR_KEY_SIZE};
+
+mod session;
+mod theme;
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Parser)]
 #[command(name = "windfish-tui")]
 #[command(about = "TUI editor for Bitcoin mempool.dat files", long_about = None)]
 struct Args {
-    /// Input mempool.dat file path
+    /// Input mempool.dat file path, or `-` to read it from stdin along with
+    /// a command-line operation
     #[arg(short, long)]
     input: PathBuf,
 
-    /// Output mempool.dat file path
-    #[arg(short, long)]
-    output: PathBuf,
+    /// Output mempool.dat file path, or `-` to write it to stdout along with
+    /// a command-line edit
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["summary", "json", "in_place", "dry_run", "export_csv"]
+    )]
+    output: Option<PathBuf>,
+
+    /// Save edits back to the input file instead of a separate --output
+    #[arg(long, conflicts_with = "output")]
+    in_place: bool,
+
+    /// Delete the transaction with this TXID and save without starting the
+    /// TUI (repeatable)
+    #[arg(long, value_name = "TXID")]
+    delete: Vec<Txid>,
+
+    /// Insert this raw transaction hex and save without starting the TUI
+    /// (repeatable)
+    #[arg(long, value_name = "HEX")]
+    insert_hex: Vec<String>,
+
+    /// Insert the raw transaction hex found on each line of this file and
+    /// save without starting the TUI (repeatable)
+    #[arg(long, value_name = "PATH")]
+    insert_file: Vec<PathBuf>,
+
+    /// Write transaction metadata as CSV to this path without starting the TUI
+    #[arg(long, value_name = "PATH")]
+    export_csv: Option<PathBuf>,
+
+    /// Delete transactions in the TUI without asking for confirmation
+    #[arg(long)]
+    no_confirm: bool,
+
+    /// Print a summary of the input without starting the TUI
+    #[arg(long)]
+    summary: bool,
+
+    /// Print the summary as a JSON object without starting the TUI
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "summary",
+            "dry_run",
+            "delete",
+            "insert_hex",
+            "insert_file",
+            "export_csv",
+            "version_convert",
+        ]
+    )]
+    json: bool,
+
+    /// Rewrite the dump in this format version and save it without starting
+    /// the TUI
+    #[arg(long, value_enum, value_name = "VERSION")]
+    version_convert: Option<DumpVersion>,
+
+    /// XOR key for `--version-convert v2`, as 16 hex characters; a random key
+    /// is generated and printed to stderr if omitted
+    #[arg(long, value_name = "HEX", value_parser = parse_xor_key, requires = "version_convert")]
+    xor_key: Option<[u8; XOR_KEY_SIZE]>,
+
+    /// Apply the command-line edits and report what would be saved, without
+    /// writing any file
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Network the dump belongs to (bitcoin, testnet, testnet4, signet or
+    /// regtest), shown in the header as a reminder
+    #[arg(long)]
+    network: Option<Network>,
+
+    /// Color theme
+    #[arg(long, value_enum, default_value_t)]
+    theme: ThemeName,
+
+    /// Keep the background and title still and redraw only about once a
+    /// second, e.g. over SSH or on battery (implied when stdout is not a
+    /// terminal)
+    #[arg(long)]
+    no_animation: bool,
+
+    /// Milliseconds between redraws, 10 to 1000; lower is smoother, higher
+    /// saves CPU [default: 50, or 1000 without animation]
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(10..=1000))]
+    tick_ms: Option<u64>,
+
+    /// Neither restore nor save the selection and sort order, which are
+    /// otherwise kept per input file under the user's config directory
+    #[arg(long)]
+    no_session: bool,
 }
 
+impl Args {
+    /// Whether any edit was requested on the command line.
+    const fn has_edits(&self) -> bool {
+        !self.delete.is_empty() || !self.insert_hex.is_empty() || !self.insert_file.is_empty()
+    }
+
+    /// Where edits are saved: `--output`, or the input itself with
+    /// `--in-place`.
+    fn output_path(&self) -> Option<&Path> {
+        self.output
+            .as_deref()
+            .or_else(|| self.in_place.then_some(self.input.as_path()))
+    }
+
+    /// Whether any command-line operation was requested, in which case the
+    /// TUI is skipped.
+    const fn is_non_interactive(&self) -> bool {
+        self.has_edits()
+            || self.version_convert.is_some()
+            || self.export_csv.is_some()
+            || self.summary
+            || self.json
+            || self.dry_run
+    }
+
+    /// Whether the dump is read from stdin.
+    fn reads_stdin(&self) -> bool {
+        is_stdio(&self.input)
+    }
+
+    /// Whether the edited dump is written to stdout.
+    fn writes_stdout(&self) -> bool {
+        self.output_path().is_some_and(is_stdio)
+    }
+
+    /// Rejects combinations that `-` as a path makes meaningless: the TUI
+    /// needs the terminal, and stdout cannot carry both a dump and a report.
+    fn check_stdio(&self) -> Result<(), &'static str> {
+        if (self.reads_stdin() || self.writes_stdout()) && !self.is_non_interactive() {
+            return Err(
+                "`-` for --input or --output requires a command-line operation \
+                        such as --delete, --insert-hex or --summary",
+            );
+        }
+        if self.reads_stdin() && self.in_place {
+            return Err("--in-place cannot be used when reading from stdin");
+        }
+        if self.writes_stdout() && (self.summary || self.json) {
+            return Err("--summary and --json cannot be combined with --output -");
+        }
+        Ok(())
+    }
+
+    /// Prints a progress message, on stderr when stdout carries the dump.
+    fn report(&self, message: &str) {
+        if self.writes_stdout() {
+            eprintln!("{message}");
+        } else {
+            println!("{message}");
+        }
+    }
+}
+
+/// Dump format for `--version-convert`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum DumpVersion {
+    /// Unobfuscated, for Bitcoin Core releases before 28.0 and other tools
+    V1,
+    /// Obfuscated with an XOR key, loaded by Bitcoin Core 28.0 and later
+    V2,
+}
+
+/// Parses `--xor-key`, which must be exactly [`XOR_KEY_SIZE`] bytes of hex.
+fn parse_xor_key(hex: &str) -> Result<[u8; XOR_KEY_SIZE], String> {
+    let bytes = hex::decode(hex).map_err(|e| format!("invalid hex: {e}"))?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| format!("expected {XOR_KEY_SIZE} bytes, got {}", bytes.len()))
+}
+
+/// Whether `path` is `-`, standing for stdin or stdout.
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+#[allow(clippy::struct_excessive_bools)]
 struct App {
     mempool: MempoolSerde,
+    /// Indices into `mempool.txs` in display order
+    view: Vec<usize>,
+    sort_mode: SortMode,
+    focus: Focus,
+    /// TXID hex prefix the view is narrowed to
+    filter: String,
+    /// Only list transactions flagged by `MempoolSerde::anomalies`
+    anomalies_only: bool,
+    /// Only list transactions paying this address, kept as typed for the
+    /// list title
+    address_filter: Option<(String, ScriptBuf)>,
     list_state: ListState,
+    /// Selection in the unbroadcast TXID popup
+    unbroadcast_state: ListState,
+    /// Rows visible in the list, updated on every draw
+    page_size: usize,
+    /// Screen area of the list, updated on every draw so clicks can be
+    /// hit-tested
+    list_area: Rect,
     output_path: PathBuf,
+    /// Whether the mempool was edited since it was loaded or last saved
+    dirty: bool,
+    /// Network given on the command line, if any
+    network: Option<Network>,
+    /// Ask before deleting a transaction
+    confirm_delete: bool,
+    theme: &'static Theme,
     mode: Mode,
+    /// First key of a two-key command, such as the first `g` of `gg`
+    pending_key: Option<char>,
+    /// Count typed before a motion or delete, as in `10j`; kept while a
+    /// counted delete awaits confirmation
+    pending_count: Option<usize>,
     input_buffer: String,
+    /// Why the last submitted input was rejected, shown in the input popup
+    input_error: Option<String>,
+    /// First visible line of the raw hex popup
+    hex_scroll: u16,
+    /// First visible line of the help popup
+    help_scroll: u16,
+    /// First visible line of the details panel, and the transaction it was
+    /// scrolled on; moving the selection starts the details from the top
+    details_scroll: (Option<Txid>, u16),
     status_message: Option<(String, Instant)>,
+    /// Whether the theme's animation runs; off with `--no-animation`
+    animate: bool,
     animation_tick: u64,
+    /// Time between ticks, which advance the animation and bound how long
+    /// the event loop waits for input; set with `--tick-ms`
+    tick_rate: Duration,
+    /// Summary shown in the header, recomputed only after the mempool changes
+    stats: Option<Stats>,
+    /// Ancestor and descendant counts of the last selected transaction
+    package: Option<(Txid, usize, usize)>,
+    /// Opened on first copy and kept alive, as X11 clipboards only serve
+    /// their contents while the owner is running
+    #[cfg(feature = "clipboard")]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+/// Aggregate figures for the header.
+struct Stats {
+    tx_count: usize,
+    total_vsize: usize,
+    total_fee_delta: i128,
+    unbroadcast_count: usize,
+    /// Indices of every copy of each TXID that appears more than once
+    duplicates: HashMap<Txid, Vec<usize>>,
+}
+
+impl Stats {
+    fn compute(mempool: &MempoolSerde) -> Self {
+        Self {
+            tx_count: mempool.txs.len(),
+            total_vsize: mempool.total_vsize(),
+            total_fee_delta: mempool
+                .txs
+                .iter()
+                .map(|txn| i128::from(txn.fee_delta))
+                .sum(),
+            unbroadcast_count: mempool.unbroadcast_txids.len(),
+            duplicates: mempool.duplicates().into_iter().collect(),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq)]
 enum Mode {
     Normal,
     Insert,
+    ImportFile,
+    ExportTx,
+    Filter,
+    EditFeeDelta,
+    EditTime,
+    ConfirmDelete,
+    ConfirmQuit,
+    RawHex,
+    Unbroadcast,
+    Command,
+    Help,
+}
+
+/// The pane navigation keys act on, switched with Tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    List,
+    Details,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortMode {
+    Original,
+    FeeDelta,
+    Time,
+    Vsize,
+}
+
+impl SortMode {
+    const fn next(self) -> Self {
+        match self {
+            Self::Original => Self::FeeDelta,
+            Self::FeeDelta => Self::Time,
+            Self::Time => Self::Vsize,
+            Self::Vsize => Self::Original,
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Original => "file order",
+            Self::FeeDelta => "fee delta",
+            Self::Time => "time",
+            Self::Vsize => "vsize",
+        }
+    }
+
+    /// Stable identifier saved in sessions.
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Original => "original",
+            Self::FeeDelta => "fee_delta",
+            Self::Time => "time",
+            Self::Vsize => "vsize",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [Self::Original, Self::FeeDelta, Self::Time, Self::Vsize]
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
 }
 
 impl App {
-    fn new(mempool: MempoolSerde, output_path: PathBuf) -> Self {
+    fn new(
+        mempool: MempoolSerde,
+        output_path: PathBuf,
+        network: Option<Network>,
+        confirm_delete: bool,
+        theme: &'static Theme,
+    ) -> Self {
         let mut list_state = ListState::default();
         if !mempool.txs.is_empty() {
             list_state.select(Some(0));
         }
+        let view = (0..mempool.txs.len()).collect();
         Self {
             mempool,
+            view,
+            sort_mode: SortMode::Original,
+            focus: Focus::List,
+            filter: String::new(),
+            anomalies_only: false,
+            address_filter: None,
             list_state,
+            unbroadcast_state: ListState::default(),
+            page_size: 1,
+            list_area: Rect::default(),
             output_path,
+            dirty: false,
+            network,
+            confirm_delete,
+            theme,
             mode: Mode::Normal,
+            pending_key: None,
+            pending_count: None,
             input_buffer: String::new(),
+            input_error: None,
+            hex_scroll: 0,
+            help_scroll: 0,
+            details_scroll: (None, 0),
             status_message: None,
+            animate: theme.animated,
             animation_tick: 0,
+            // Without animation, ticks only need to expire status messages.
+            tick_rate: Duration::from_millis(if theme.animated { 50 } else { 1_000 }),
+            stats: None,
+            package: None,
+            #[cfg(feature = "clipboard")]
+            clipboard: None,
         }
     }
 
-    fn selected_tx(&self) -> Option<&Txn> {
+    /// Index into `mempool.txs` of the selected transaction.
+    fn selected_index(&self) -> Option<usize> {
         self.list_state
             .selected()
-            .and_then(|i| self.mempool.txs.get(i))
+            .and_then(|pos| self.view.get(pos).copied())
+    }
+
+    fn stats(&mut self) -> &Stats {
+        self.stats
+            .get_or_insert_with(|| Stats::compute(&self.mempool))
+    }
+
+    fn selected_tx(&self) -> Option<&Txn> {
+        self.selected_index().and_then(|i| self.mempool.txs.get(i))
+    }
+
+    /// TXIDs of `mempool.txs`, in file order.
+    fn txids(&self) -> Vec<Txid> {
+        self.mempool.txs.iter().map(Txn::txid).collect()
+    }
+
+    fn selected_txid(&self) -> Option<Txid> {
+        self.selected_tx().map(Txn::txid)
+    }
+
+    /// Ancestor and descendant counts of the selected transaction, recomputed
+    /// only when the selection or the mempool changes.
+    fn package(&mut self) -> Option<(usize, usize)> {
+        let txid = self.selected_txid()?;
+        if self.package.is_none_or(|(cached, ..)| cached != txid) {
+            let ancestors = self.mempool.ancestors(&txid).len();
+            let descendants = self.mempool.descendants(&txid).len();
+            self.package = Some((txid, ancestors, descendants));
+        }
+        self.package
+            .map(|(_, ancestors, descendants)| (ancestors, descendants))
+    }
+
+    /// Drops everything derived from the mempool after it was edited, and
+    /// marks it as needing a save.
+    fn mempool_changed(&mut self) {
+        self.dirty = true;
+        self.stats = None;
+        self.package = None;
+    }
+
+    /// First visible line of the details panel for the selected transaction.
+    fn details_scroll(&self) -> u16 {
+        let (txid, scroll) = self.details_scroll;
+        if txid.is_some() && txid == self.selected_txid() {
+            scroll
+        } else {
+            0
+        }
+    }
+
+    /// Scrolls the details panel by `lines`, stopping at the top; `ui` stops
+    /// it at the last line.
+    fn scroll_details(&mut self, lines: i16) {
+        let scroll = self.details_scroll().saturating_add_signed(lines);
+        self.details_scroll = (self.selected_txid(), scroll);
+    }
+
+    const fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::List => Focus::Details,
+            Focus::Details => Focus::List,
+        };
+    }
+
+    /// Selects the transaction at `mempool.txs[i]`, wherever it is in the view.
+    fn select_index(&mut self, i: usize) {
+        let pos = self.view.iter().position(|&v| v == i);
+        self.list_state.select(pos);
+    }
+
+    /// Rebuilds `view` from `mempool.txs` using the current filter and sort mode.
+    fn refresh_view(&mut self) {
+        let txids = self.txids();
+        let flagged: Option<HashSet<Txid>> = self.anomalies_only.then(|| {
+            self.mempool
+                .anomalies(&AnomalyConfig::default())
+                .into_iter()
+                .map(|(txid, _)| txid)
+                .collect()
+        });
+        let paying: Option<HashSet<Txid>> = self
+            .address_filter
+            .as_ref()
+            .map(|(_, script)| self.mempool.spending_script(script).into_iter().collect());
+        let txs = &self.mempool.txs;
+        let filter = self.filter.to_lowercase();
+        let mut view: Vec<usize> = (0..txs.len())
+            .filter(|&i| filter.is_empty() || txids[i].to_string().starts_with(&filter))
+            .filter(|&i| {
+                flagged
+                    .as_ref()
+                    .is_none_or(|flagged| flagged.contains(&txids[i]))
+            })
+            .filter(|&i| {
+                paying
+                    .as_ref()
+                    .is_none_or(|paying| paying.contains(&txids[i]))
+            })
+            .collect();
+        match self.sort_mode {
+            SortMode::Original => {}
+            SortMode::FeeDelta => view.sort_by_key(|&i| std::cmp::Reverse(txs[i].fee_delta)),
+            SortMode::Time => view.sort_by_key(|&i| txs[i].time),
+            SortMode::Vsize => view.sort_by_key(|&i| std::cmp::Reverse(txs[i].vsize())),
+        }
+        self.view = view;
+    }
+
+    /// Runs a `:` command: a 1-based transaction number to jump to, `txid`
+    /// followed by a TXID or TXID prefix to find, `addr` followed by an
+    /// address to list only the transactions paying it, or `version` to
+    /// change the dump format.
+    fn run_command(&mut self, command: &str) -> Result<(), String> {
+        let command = command.trim();
+        if command.is_empty() {
+            return Ok(());
+        }
+        let (verb, args) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        let args = args.trim();
+
+        let i = match verb {
+            "addr" => return self.filter_address(args),
+            "version" => return self.change_version(args),
+            "txid" => {
+                let hex = args.to_lowercase();
+                if hex.is_empty() {
+                    return Err("Usage: :txid <hex>".to_string());
+                }
+                self.txids()
+                    .iter()
+                    .position(|txid| txid.to_string().starts_with(&hex))
+                    .ok_or_else(|| format!("No transaction matches {hex}"))?
+            }
+            _ => {
+                let n: usize = command
+                    .parse()
+                    .map_err(|_| format!("Unknown command: {command}"))?;
+                n.checked_sub(1)
+                    .filter(|&i| i < self.mempool.txs.len())
+                    .ok_or_else(|| format!("No transaction {n}"))?
+            }
+        };
+
+        if !self.view.contains(&i) {
+            return Err(format!("Transaction {} is hidden by the filter", i + 1));
+        }
+        self.select_index(i);
+        Ok(())
+    }
+
+    /// Re-filters the view after the filter text changed, selecting the first match.
+    fn apply_filter(&mut self) {
+        self.refresh_view();
+        self.list_state
+            .select(if self.view.is_empty() { None } else { Some(0) });
+    }
+
+    /// Narrows the view to transactions paying `address`, or lifts that
+    /// filter if `address` is empty. Addresses of any network are accepted,
+    /// since a dump does not say which network it came from.
+    fn filter_address(&mut self, address: &str) -> Result<(), String> {
+        if address.is_empty() {
+            self.address_filter = None;
+            self.apply_filter();
+            self.set_status("Showing all transactions".to_string());
+            return Ok(());
+        }
+
+        let script = Address::from_str(address)
+            .map_err(|e| format!("Invalid address: {e}"))?
+            .assume_checked()
+            .script_pubkey();
+        self.address_filter = Some((address.to_string(), script));
+        self.apply_filter();
+        self.set_status(format!(
+            "{} transaction(s) paying {address}",
+            self.view.len()
+        ));
+        Ok(())
+    }
+
+    /// Handles `:version 1` and `:version 2 [<key hex>]`. Without a key, a V2
+    /// dump keeps its key and a V1 dump gets a random one, as with
+    /// `--version-convert`.
+    fn change_version(&mut self, args: &str) -> Result<(), String> {
+        const USAGE: &str = "Usage: :version 1 | :version 2 [<xor key hex>]";
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args[..] {
+            ["1"] => {
+                self.mempool.downgrade_to_v1();
+                self.mempool_changed();
+                self.set_status(
+                    "Dump version set to 1: transactions will be written unobfuscated".to_string(),
+                );
+            }
+            ["2", ..] if args.len() <= 2 => {
+                let xor_key = match args.get(1) {
+                    Some(hex) => parse_xor_key(hex).map_err(|e| format!("Invalid XOR key: {e}"))?,
+                    None => self
+                        .mempool
+                        .xor_key()
+                        .copied()
+                        .unwrap_or_else(random_xor_key),
+                };
+                self.mempool.upgrade_to_v2(xor_key);
+                self.mempool_changed();
+                self.set_status(format!(
+                    "Dump version set to 2 with XOR key {}",
+                    hex::encode(xor_key)
+                ));
+            }
+            _ => return Err(USAGE.to_string()),
+        }
+        Ok(())
+    }
+
+    fn toggle_anomalies(&mut self) {
+        self.anomalies_only = !self.anomalies_only;
+        self.apply_filter();
+        self.set_status(if self.anomalies_only {
+            format!("{} transaction(s) with anomalies", self.view.len())
+        } else {
+            "Showing all transactions".to_string()
+        });
+    }
+
+    /// The sort order and selection to remember for the next run.
+    fn session(&self) -> Session {
+        Session {
+            sort_mode: self.sort_mode,
+            txid: self.selected_txid(),
+        }
+    }
+
+    /// Restores a session saved by an earlier run, selecting its transaction
+    /// if it is still in the dump.
+    fn restore(&mut self, session: Session) {
+        self.sort_mode = session.sort_mode;
+        self.refresh_view();
+        let found = session
+            .txid
+            .and_then(|txid| self.txids().iter().position(|&t| t == txid));
+        match found {
+            Some(i) => self.select_index(i),
+            None => self
+                .list_state
+                .select(if self.view.is_empty() { None } else { Some(0) }),
+        }
+    }
+
+    fn cycle_sort(&mut self) {
+        let selected = self.selected_index();
+        self.sort_mode = self.sort_mode.next();
+        self.refresh_view();
+
+        // Keep the same transaction selected across re-sorts.
+        if let Some(i) = selected {
+            self.select_index(i);
+        }
+        self.set_status(format!("Sorted by {}", self.sort_mode.label()));
     }
 
     fn next(&mut self) {
-        let len = self.mempool.txs.len();
+        let len = self.view.len();
         if len == 0 {
             return;
         }
@@ -84,7 +717,7 @@ impl App {
     }
 
     fn previous(&mut self) {
-        let len = self.mempool.txs.len();
+        let len = self.view.len();
         if len == 0 {
             return;
         }
@@ -95,40 +728,345 @@ impl App {
         self.list_state.select(Some(i));
     }
 
-    fn delete_selected(&mut self) {
-        if let Some(i) = self.list_state.selected()
-            && i < self.mempool.txs.len()
-        {
-            self.mempool.txs.remove(i);
-            self.set_status("Transaction deleted".to_string());
-            if self.mempool.txs.is_empty() {
+    fn page_down(&mut self) {
+        if self.view.is_empty() {
+            return;
+        }
+        let i = self
+            .list_state
+            .selected()
+            .map_or(0, |i| (i + self.page_size).min(self.view.len() - 1));
+        self.list_state.select(Some(i));
+    }
+
+    fn page_up(&mut self) {
+        if self.view.is_empty() {
+            return;
+        }
+        let i = self
+            .list_state
+            .selected()
+            .map_or(0, |i| i.saturating_sub(self.page_size));
+        self.list_state.select(Some(i));
+    }
+
+    const fn first(&mut self) {
+        if !self.view.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    const fn last(&mut self) {
+        if !self.view.is_empty() {
+            self.list_state.select(Some(self.view.len() - 1));
+        }
+    }
+
+    /// Moves the selection by `step` rows, stopping at either end of the list.
+    fn scroll(&mut self, step: isize) {
+        if self.view.is_empty() {
+            return;
+        }
+        let i = self.list_state.selected().map_or(0, |i| {
+            i.saturating_add_signed(step).min(self.view.len() - 1)
+        });
+        self.list_state.select(Some(i));
+    }
+
+    /// Appends pasted text to the input being typed. Transaction hex keeps
+    /// its line breaks, which [`decode_tx_hex`] ignores, so a paste wrapped
+    /// over several lines is inserted whole rather than submitted at its
+    /// first line break; other inputs are single lines and drop them.
+    fn paste(&mut self, text: &str) {
+        let line = text.replace(['\r', '\n'], "");
+        match self.mode {
+            Mode::Insert => {
+                self.input_buffer.push_str(text);
+                self.input_error = None;
+            }
+            Mode::ImportFile | Mode::ExportTx | Mode::EditFeeDelta | Mode::EditTime => {
+                self.input_buffer.push_str(&line);
+                self.input_error = None;
+            }
+            Mode::Command => self.input_buffer.push_str(&line),
+            Mode::Filter => {
+                self.filter.push_str(&line);
+                self.apply_filter();
+            }
+            _ => {}
+        }
+    }
+
+    /// Selects the list row under a left click, focusing the list, and
+    /// scrolls the focused pane with the mouse wheel.
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let rows = self.list_area.inner(Margin::new(1, 1));
+                if !rows.contains(Position::new(mouse.column, mouse.row)) {
+                    return;
+                }
+                self.focus = Focus::List;
+                let pos = self.list_state.offset() + usize::from(mouse.row - rows.y);
+                if pos < self.view.len() {
+                    self.list_state.select(Some(pos));
+                }
+            }
+            MouseEventKind::ScrollDown if self.focus == Focus::Details => self.scroll_details(1),
+            MouseEventKind::ScrollUp if self.focus == Focus::Details => self.scroll_details(-1),
+            MouseEventKind::ScrollDown => self.scroll(1),
+            MouseEventKind::ScrollUp => self.scroll(-1),
+            _ => {}
+        }
+    }
+
+    /// Copies the selected TXID to the clipboard, or shows it in the status
+    /// bar when no clipboard is available.
+    fn copy_txid(&mut self) {
+        let Some(txid) = self.selected_txid() else {
+            return;
+        };
+        match self.copy_to_clipboard(&txid.to_string()) {
+            Ok(()) => self.set_status(format!("Copied {txid}")),
+            Err(_) => self.set_status(format!("TXID: {txid}")),
+        }
+    }
+
+    #[cfg(feature = "clipboard")]
+    fn copy_to_clipboard(&mut self, text: &str) -> Result<(), String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        self.clipboard
+            .as_mut()
+            .map_or(Ok(()), |clipboard| clipboard.set_text(text))
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    #[allow(clippy::unused_self)]
+    fn copy_to_clipboard(&self, _text: &str) -> Result<(), String> {
+        Err("clipboard support not enabled".to_string())
+    }
+
+    /// Adds the selected transaction to the unbroadcast set, or removes it if
+    /// already there.
+    fn toggle_unbroadcast(&mut self) {
+        let Some(txid) = self.selected_txid() else {
+            return;
+        };
+        if self.mempool.unbroadcast_txids.remove(&txid) {
+            self.set_status("Removed from unbroadcast set".to_string());
+        } else {
+            self.mempool.unbroadcast_txids.insert(txid);
+            self.set_status("Added to unbroadcast set".to_string());
+        }
+        self.mempool_changed();
+    }
+
+    /// Unbroadcast TXIDs in the order the popup lists them.
+    fn unbroadcast_list(&self) -> Vec<Txid> {
+        let mut txids: Vec<Txid> = self.mempool.unbroadcast_txids.iter().copied().collect();
+        txids.sort_unstable();
+        txids
+    }
+
+    fn open_unbroadcast(&mut self) {
+        self.mode = Mode::Unbroadcast;
+        let selected = (!self.mempool.unbroadcast_txids.is_empty()).then_some(0);
+        self.unbroadcast_state.select(selected);
+    }
+
+    /// Moves the popup selection by `step`, wrapping at either end.
+    fn step_unbroadcast(&mut self, step: isize) {
+        let len = self.mempool.unbroadcast_txids.len();
+        if len == 0 {
+            return;
+        }
+        let i = self
+            .unbroadcast_state
+            .selected()
+            .map_or(0, |i| (i + len).wrapping_add_signed(step) % len);
+        self.unbroadcast_state.select(Some(i));
+    }
+
+    /// Removes the TXID highlighted in the popup from the unbroadcast set.
+    fn remove_unbroadcast(&mut self) {
+        let Some(pos) = self.unbroadcast_state.selected() else {
+            return;
+        };
+        let Some(txid) = self.unbroadcast_list().get(pos).copied() else {
+            return;
+        };
+        self.mempool.unbroadcast_txids.remove(&txid);
+        self.mempool_changed();
+        let len = self.mempool.unbroadcast_txids.len();
+        self.unbroadcast_state.select(if len == 0 {
+            None
+        } else {
+            Some(pos.min(len - 1))
+        });
+        self.set_status(format!(
+            "Removed {} from unbroadcast set",
+            short_txid(&txid)
+        ));
+    }
+
+    /// Empties the unbroadcast set.
+    fn clear_unbroadcast(&mut self) {
+        let cleared = self.mempool.clear_unbroadcast();
+        self.mempool_changed();
+        self.unbroadcast_state.select(None);
+        self.set_status(format!("Cleared {cleared} unbroadcast TXID(s)"));
+    }
+
+    fn clear_deltas(&mut self) {
+        let cleared = self.mempool.clear_deltas();
+        let selected = self.selected_index();
+        self.mempool_changed();
+        self.refresh_view();
+        if let Some(i) = selected {
+            self.select_index(i);
+        }
+        self.set_status(format!("Cleared {cleared} fee delta(s)"));
+    }
+
+    /// Deletes `count` listed transactions from the selection down, stopping
+    /// at the end of the list.
+    fn delete_selected(&mut self, count: usize) {
+        let Some(pos) = self.list_state.selected() else {
+            return;
+        };
+        let txids = self.txids();
+        let doomed: Vec<Txid> = self
+            .view
+            .iter()
+            .skip(pos)
+            .take(count)
+            .map(|&i| txids[i])
+            .collect();
+        if !doomed.is_empty() {
+            for txid in &doomed {
+                self.mempool.remove_transaction(txid);
+            }
+            self.mempool_changed();
+            self.refresh_view();
+            self.set_status(if doomed.len() == 1 {
+                "Transaction deleted".to_string()
+            } else {
+                format!("{} transactions deleted", doomed.len())
+            });
+            if self.view.is_empty() {
                 self.list_state.select(None);
-            } else if i >= self.mempool.txs.len() {
-                self.list_state.select(Some(self.mempool.txs.len() - 1));
+            } else if pos >= self.view.len() {
+                self.list_state.select(Some(self.view.len() - 1));
             }
         }
     }
 
     fn insert_tx(&mut self, hex: &str) -> Result<(), String> {
-        let bytes = hex::decode(hex.trim()).map_err(|e| format!("Invalid hex: {e}"))?;
-        let tx: Transaction = Transaction::consensus_decode(&mut bytes.as_slice())
-            .map_err(|e| format!("Invalid transaction: {e}"))?;
-
-        let txn = Txn {
-            tx,
-            time: chrono::Utc::now().timestamp(),
-            fee_delta: 0,
-        };
-        self.mempool.txs.push(txn);
-        self.list_state.select(Some(self.mempool.txs.len() - 1));
+        let tx = decode_tx_hex(hex)?;
+
+        self.mempool
+            .add_transaction(tx, chrono::Utc::now().timestamp(), 0)
+            .map_err(|e| e.to_string())?;
+        self.mempool_changed();
+        self.refresh_view();
+        self.select_index(self.mempool.txs.len() - 1);
         self.set_status("Transaction inserted".to_string());
         Ok(())
     }
 
-    fn save(&self) -> Result<(), String> {
+    /// Inserts every transaction listed in the file at `path`, reporting how
+    /// many were inserted and how many failed, with the first failure's
+    /// reason.
+    fn import_file(&mut self, path: &str) -> Result<(), String> {
+        let path = path.trim();
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+
+        let now = chrono::Utc::now().timestamp();
+        let (mut inserted, mut failed) = (0, 0);
+        let mut first_error = None;
+        for hex in tx_hex_lines(&contents) {
+            match decode_tx_hex(hex).and_then(|tx| {
+                self.mempool
+                    .add_transaction(tx, now, 0)
+                    .map_err(|e| e.to_string())
+            }) {
+                Ok(_) => inserted += 1,
+                Err(e) => {
+                    failed += 1;
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        if inserted > 0 {
+            self.mempool_changed();
+            self.refresh_view();
+        }
+        let first_error = first_error.map_or_else(String::new, |e| format!(" (first: {e})"));
+        self.set_status(format!(
+            "Imported {inserted} transaction(s), {failed} failed{first_error}"
+        ));
+        Ok(())
+    }
+
+    /// Writes the selected transaction to `path`: raw bytes if the path ends
+    /// in `.bin`, hex otherwise.
+    fn export_tx(&mut self, path: &str) -> Result<(), String> {
+        let path = path.trim();
+        let txn = self.selected_tx().ok_or("No transaction selected")?;
+        let contents = if Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("bin"))
+        {
+            serialize(&txn.tx)
+        } else {
+            format!("{}\n", serialize_hex(&txn.tx)).into_bytes()
+        };
+        std::fs::write(path, contents).map_err(|e| format!("Failed to write {path}: {e}"))?;
+
+        let txid = self.selected_txid().ok_or("No transaction selected")?;
+        self.set_status(format!("Exported {} to {path}", short_txid(&txid)));
+        Ok(())
+    }
+
+    fn edit_fee_delta(&mut self, input: &str) -> Result<(), String> {
+        let fee_delta: i64 = input
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid fee delta: {e}"))?;
+        let i = self.selected_index().ok_or("No transaction selected")?;
+        let txid = self.selected_txid().ok_or("No transaction selected")?;
+
+        self.mempool.set_fee_delta(&txid, fee_delta);
+        self.mempool_changed();
+        self.refresh_view();
+        self.select_index(i);
+        self.set_status(format!("Fee delta set to {fee_delta} sat"));
+        Ok(())
+    }
+
+    fn edit_time(&mut self, input: &str) -> Result<(), String> {
+        let time = parse_time(input)?;
+        let i = self.selected_index().ok_or("No transaction selected")?;
+
+        self.mempool.txs[i].time = time;
+        self.mempool_changed();
+        self.refresh_view();
+        self.select_index(i);
+        self.set_status(format!("Time set to {}", format_time(time)));
+        Ok(())
+    }
+
+    fn save(&mut self) -> Result<(), String> {
         self.mempool
             .write_to_file(&self.output_path)
-            .map_err(|e| format!("Save failed: {e}"))
+            .map_err(|e| format!("Save failed: {e}"))?;
+        self.dirty = false;
+        Ok(())
     }
 
     fn set_status(&mut self, msg: String) {
@@ -145,80 +1083,781 @@ impl App {
     }
 }
 
+/// Parses a unix timestamp or a `YYYY-MM-DD HH:MM:SS` UTC date.
+fn parse_time(input: &str) -> Result<i64, String> {
+    let input = input.trim();
+    if let Ok(timestamp) = input.parse::<i64>() {
+        return Ok(timestamp);
+    }
+    chrono::NaiveDateTime::parse_from_str(input.trim_end_matches(" UTC"), "%Y-%m-%d %H:%M:%S")
+        .map(|dt| dt.and_utc().timestamp())
+        .map_err(|e| format!("Invalid time (use unix seconds or YYYY-MM-DD HH:MM:SS): {e}"))
+}
+
+fn format_time(time: i64) -> String {
+    chrono::DateTime::from_timestamp(time, 0).map_or_else(
+        || "Unknown".to_string(),
+        |dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+    )
+}
+
+/// Converts a key repeat count into lines to scroll the details panel.
+fn scroll_lines(count: usize) -> i16 {
+    i16::try_from(count).unwrap_or(i16::MAX)
+}
+
+/// Abbreviates a TXID to its first and last eight hex characters.
+fn short_txid(txid: &Txid) -> String {
+    abbreviate(&txid.to_string())
+}
+
+/// Abbreviates `id` to its first and last eight characters, returning it
+/// whole if that would not make it shorter.
+fn abbreviate(id: &str) -> String {
+    let chars: Vec<char> = id.chars().collect();
+    if chars.len() <= 19 {
+        return id.to_string();
+    }
+    let head: String = chars[..8].iter().collect();
+    let tail: String = chars[chars.len() - 8..].iter().collect();
+    format!("{head}...{tail}")
+}
+
+/// Yields the transaction hex lines of a file, skipping blank lines and
+/// `#` comments.
+fn tx_hex_lines(contents: &str) -> impl Iterator<Item = &str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Decodes raw transaction hex, ignoring whitespace anywhere in it and an
+/// optional `0x` prefix so wrapped or prefixed pastes still decode, and
+/// rejects transactions that fail [`windfish::basic_sanity`].
+fn decode_tx_hex(hex: &str) -> Result<Transaction, String> {
+    let hex: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    let hex = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(&hex);
+    let bytes = hex::decode(hex).map_err(|e| format!("Invalid hex: {e}"))?;
+    let tx = Transaction::consensus_decode(&mut bytes.as_slice())
+        .map_err(|e| format!("Invalid transaction: {e}"))?;
+    windfish::basic_sanity(&tx).map_err(|e| format!("Invalid transaction: {e}"))?;
+    Ok(tx)
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    if let Err(e) = args.check_stdio() {
+        eprintln!("{e}");
+        std::process::exit(2);
+    }
+
+    let mempool = match load(&args.input) {
+        Ok(mempool) => mempool,
+        Err(e) => {
+            eprintln!("Failed to load {}: {e}", args.input.display());
+            std::process::exit(1);
+        }
+    };
+
+    if args.is_non_interactive() {
+        if let Err(e) = run_non_interactive(mempool, &args) {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let output = args
+        .output_path()
+        .expect(
+            "clap requires --output or --in-place unless --summary, --json, --dry-run or \
+             --export-csv is given",
+        )
+        .to_path_buf();
+    let mut app = App::new(
+        mempool,
+        output,
+        args.network,
+        !args.no_confirm,
+        args.theme.theme(),
+    );
+    if args.no_animation || !io::stdout().is_terminal() {
+        app.animate = false;
+        app.tick_rate = Duration::from_secs(1);
+    }
+    if let Some(ms) = args.tick_ms {
+        app.tick_rate = Duration::from_millis(ms);
+    }
+    let session_path = (!args.no_session)
+        .then(|| Session::path_for(&args.input))
+        .flatten();
+    if let Some(session) = session_path.as_deref().and_then(Session::load) {
+        app.restore(session);
+    }
 
-    let mempool = MempoolSerde::new(&args.input)?;
-    let mut app = App::new(mempool, args.output);
+    // Leave raw mode and the alternate screen before a panic message is
+    // printed, so a crash does not garble the user's shell.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+    }));
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let tick_rate = Duration::from_millis(50);
     let mut last_tick = Instant::now();
 
     loop {
         terminal.draw(|f| ui(f, &mut app))?;
 
-        let timeout = tick_rate.saturating_sub(last_tick.elapsed());
-        if crossterm::event::poll(timeout)?
-            && let Event::Key(key) = event::read()?
+        let timeout = app.tick_rate.saturating_sub(last_tick.elapsed());
+        let event = event::poll(timeout)?.then(event::read).transpose()?;
+
+        if let Some(Event::Mouse(mouse)) = event
+            && app.mode == Mode::Normal
+        {
+            app.handle_mouse(mouse);
+        }
+
+        if let Some(Event::Paste(text)) = &event {
+            app.paste(text);
+        }
+
+        if let Some(Event::Key(key)) = event
             && key.kind == KeyEventKind::Press
         {
             match app.mode {
-                Mode::Normal => match key.code {
-                    KeyCode::Char('q') => break,
-                    KeyCode::Down | KeyCode::Char('j') => app.next(),
-                    KeyCode::Up | KeyCode::Char('k') => app.previous(),
-                    KeyCode::Char('d') => app.delete_selected(),
-                    KeyCode::Char('i') => {
-                        app.mode = Mode::Insert;
-                        app.input_buffer.clear();
+                Mode::Normal => {
+                    // Any key other than the one completing a command cancels
+                    // it, and a count applies only to the key right after it.
+                    let pending = app.pending_key.take();
+                    let count = app.pending_count.take();
+                    match key.code {
+                        KeyCode::Char('q') if !app.dirty => break,
+                        KeyCode::Char('q') => app.mode = Mode::ConfirmQuit,
+                        KeyCode::Char(c @ '0'..='9') if c != '0' || count.is_some() => {
+                            let digit = c.to_digit(10).map_or(0, |d| d as usize);
+                            app.pending_count =
+                                Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                        }
+                        KeyCode::Tab => app.toggle_focus(),
+                        // With the details focused, motions scroll them instead.
+                        KeyCode::Down | KeyCode::Char('j') if app.focus == Focus::Details => {
+                            app.scroll_details(scroll_lines(count.unwrap_or(1)));
+                        }
+                        KeyCode::Up | KeyCode::Char('k') if app.focus == Focus::Details => {
+                            app.scroll_details(-scroll_lines(count.unwrap_or(1)));
+                        }
+                        KeyCode::PageDown if app.focus == Focus::Details => {
+                            app.scroll_details(scroll_lines(app.page_size));
+                        }
+                        KeyCode::PageUp if app.focus == Focus::Details => {
+                            app.scroll_details(-scroll_lines(app.page_size));
+                        }
+                        KeyCode::Char('d')
+                            if app.focus == Focus::Details
+                                && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            app.scroll_details(scroll_lines(app.page_size));
+                        }
+                        KeyCode::Char('u')
+                            if app.focus == Focus::Details
+                                && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            app.scroll_details(-scroll_lines(app.page_size));
+                        }
+                        KeyCode::Home if app.focus == Focus::Details => {
+                            app.scroll_details(i16::MIN);
+                        }
+                        KeyCode::Char('g')
+                            if app.focus == Focus::Details && pending == Some('g') =>
+                        {
+                            app.scroll_details(i16::MIN);
+                        }
+                        KeyCode::End | KeyCode::Char('G') if app.focus == Focus::Details => {
+                            app.scroll_details(i16::MAX);
+                        }
+                        // Counted motions stop at the ends of the list, as in vim.
+                        KeyCode::Down | KeyCode::Char('j') => match count {
+                            Some(count) => app.scroll(count.try_into().unwrap_or(isize::MAX)),
+                            None => app.next(),
+                        },
+                        KeyCode::Up | KeyCode::Char('k') => match count {
+                            Some(count) => app.scroll(-count.try_into().unwrap_or(isize::MAX)),
+                            None => app.previous(),
+                        },
+                        KeyCode::Char('J') => app.scroll_details(scroll_lines(count.unwrap_or(1))),
+                        KeyCode::Char('K') => app.scroll_details(-scroll_lines(count.unwrap_or(1))),
+                        KeyCode::PageDown => app.page_down(),
+                        KeyCode::PageUp => app.page_up(),
+                        KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.page_down();
+                        }
+                        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.page_up();
+                        }
+                        KeyCode::Home => app.first(),
+                        KeyCode::End | KeyCode::Char('G') => app.last(),
+                        KeyCode::Char('g') if pending == Some('g') => app.first(),
+                        KeyCode::Char('g') => app.pending_key = Some('g'),
+                        KeyCode::Char('d') => {
+                            if !app.confirm_delete {
+                                app.delete_selected(count.unwrap_or(1));
+                            } else if app.selected_tx().is_some() {
+                                app.pending_count = count;
+                                app.mode = Mode::ConfirmDelete;
+                            }
+                        }
+                        KeyCode::Char('o') => app.cycle_sort(),
+                        KeyCode::Char('a') => app.toggle_anomalies(),
+                        KeyCode::Char('y') => app.copy_txid(),
+                        KeyCode::Char('u') => app.toggle_unbroadcast(),
+                        KeyCode::Char('U') => app.open_unbroadcast(),
+                        KeyCode::Char('x') if app.selected_tx().is_some() => {
+                            app.mode = Mode::RawHex;
+                            app.hex_scroll = 0;
+                        }
+                        KeyCode::Char('?') => {
+                            app.mode = Mode::Help;
+                            app.help_scroll = 0;
+                        }
+                        KeyCode::Char(':') => {
+                            app.mode = Mode::Command;
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Char('/') => {
+                            app.mode = Mode::Filter;
+                            app.filter.clear();
+                            app.apply_filter();
+                        }
+                        KeyCode::Char('i') => {
+                            app.mode = Mode::Insert;
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Char('I') => {
+                            app.mode = Mode::ImportFile;
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Char('e') if app.selected_tx().is_some() => {
+                            app.mode = Mode::ExportTx;
+                            app.input_buffer.clear();
+                        }
+                        KeyCode::Char('f') => {
+                            if let Some(fee_delta) = app.selected_tx().map(|txn| txn.fee_delta) {
+                                app.mode = Mode::EditFeeDelta;
+                                app.input_buffer = fee_delta.to_string();
+                            }
+                        }
+                        KeyCode::Char('F') => app.clear_deltas(),
+                        KeyCode::Char('t') => {
+                            if let Some(time) = app.selected_tx().map(|txn| txn.time) {
+                                app.mode = Mode::EditTime;
+                                app.input_buffer = time.to_string();
+                            }
+                        }
+                        KeyCode::Char('s') => match app.save() {
+                            Ok(()) => app.set_status("Saved successfully!".to_string()),
+                            Err(e) => app.set_status(e),
+                        },
+                        _ => {}
+                    }
+                }
+                Mode::Insert
+                | Mode::ImportFile
+                | Mode::ExportTx
+                | Mode::EditFeeDelta
+                | Mode::EditTime => {
+                    match key.code {
+                        KeyCode::Esc => {
+                            app.mode = Mode::Normal;
+                            app.input_buffer.clear();
+                            app.input_error = None;
+                        }
+                        KeyCode::Enter => {
+                            let input = app.input_buffer.clone();
+                            let result = match app.mode {
+                                Mode::EditFeeDelta => app.edit_fee_delta(&input),
+                                Mode::EditTime => app.edit_time(&input),
+                                Mode::ImportFile => app.import_file(&input),
+                                Mode::ExportTx => app.export_tx(&input),
+                                _ => app.insert_tx(&input),
+                            };
+                            // Stay in the popup on failure so the input can be fixed.
+                            match result {
+                                Ok(()) => {
+                                    app.mode = Mode::Normal;
+                                    app.input_buffer.clear();
+                                    app.input_error = None;
+                                }
+                                Err(e) => app.input_error = Some(e),
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            app.input_buffer.pop();
+                            app.input_error = None;
+                        }
+                        KeyCode::Char(c) => {
+                            app.input_buffer.push(c);
+                            app.input_error = None;
+                        }
+                        _ => {}
                     }
-                    KeyCode::Char('s') => match app.save() {
-                        Ok(()) => app.set_status("Saved successfully!".to_string()),
-                        Err(e) => app.set_status(e),
+                }
+                Mode::ConfirmQuit => match key.code {
+                    KeyCode::Char('y' | 'Y') => break,
+                    KeyCode::Char('s' | 'S') => match app.save() {
+                        Ok(()) => break,
+                        Err(e) => {
+                            app.set_status(e);
+                            app.mode = Mode::Normal;
+                        }
                     },
+                    KeyCode::Char('n' | 'N') | KeyCode::Esc => app.mode = Mode::Normal,
+                    _ => {}
+                },
+                Mode::ConfirmDelete => match key.code {
+                    KeyCode::Char('y' | 'Y') => {
+                        let count = app.pending_count.take().unwrap_or(1);
+                        app.delete_selected(count);
+                        app.mode = Mode::Normal;
+                    }
+                    KeyCode::Char('n' | 'N') | KeyCode::Esc => {
+                        app.pending_count = None;
+                        app.mode = Mode::Normal;
+                    }
+                    _ => {}
+                },
+                Mode::Unbroadcast => match key.code {
+                    KeyCode::Down | KeyCode::Char('j') => app.step_unbroadcast(1),
+                    KeyCode::Up | KeyCode::Char('k') => app.step_unbroadcast(-1),
+                    KeyCode::Char('d') => app.remove_unbroadcast(),
+                    KeyCode::Char('D') => app.clear_unbroadcast(),
+                    KeyCode::Esc | KeyCode::Char('U' | 'q') => app.mode = Mode::Normal,
+                    _ => {}
+                },
+                Mode::Help => match key.code {
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.help_scroll = app.help_scroll.saturating_add(1);
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.help_scroll = app.help_scroll.saturating_sub(1);
+                    }
+                    KeyCode::Esc | KeyCode::Char('?' | 'q') => app.mode = Mode::Normal,
                     _ => {}
                 },
-                Mode::Insert => match key.code {
+                Mode::RawHex => match key.code {
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        app.hex_scroll = app.hex_scroll.saturating_add(1);
+                    }
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        app.hex_scroll = app.hex_scroll.saturating_sub(1);
+                    }
+                    KeyCode::Esc | KeyCode::Char('x' | 'q') => app.mode = Mode::Normal,
+                    _ => {}
+                },
+                Mode::Filter => match key.code {
                     KeyCode::Esc => {
                         app.mode = Mode::Normal;
-                        app.input_buffer.clear();
+                        app.filter.clear();
+                        app.apply_filter();
                     }
+                    KeyCode::Enter => app.mode = Mode::Normal,
+                    KeyCode::Backspace => {
+                        app.filter.pop();
+                        app.apply_filter();
+                    }
+                    KeyCode::Char(c) => {
+                        app.filter.push(c);
+                        app.apply_filter();
+                    }
+                    _ => {}
+                },
+                Mode::Command => match key.code {
                     KeyCode::Enter => {
-                        let hex = app.input_buffer.clone();
-                        match app.insert_tx(&hex) {
-                            Ok(()) => app.mode = Mode::Normal,
-                            Err(e) => app.set_status(e),
+                        app.mode = Mode::Normal;
+                        let command = std::mem::take(&mut app.input_buffer);
+                        if let Err(e) = app.run_command(&command) {
+                            app.set_status(e);
                         }
-                        app.input_buffer.clear();
                     }
+                    // Backspacing past the `:` leaves the command line, as in vim.
+                    KeyCode::Backspace if app.input_buffer.is_empty() => app.mode = Mode::Normal,
                     KeyCode::Backspace => {
                         app.input_buffer.pop();
                     }
+                    KeyCode::Esc => {
+                        app.mode = Mode::Normal;
+                        app.input_buffer.clear();
+                    }
                     KeyCode::Char(c) => app.input_buffer.push(c),
                     _ => {}
                 },
             }
         }
 
-        if last_tick.elapsed() >= tick_rate {
+        if last_tick.elapsed() >= app.tick_rate {
             app.tick();
             last_tick = Instant::now();
         }
     }
 
+    restore_terminal()?;
+    terminal.show_cursor()?;
+    // Dropping our hook reinstates the default one.
+    drop(std::panic::take_hook());
+
+    if let Some(path) = session_path
+        && let Err(e) = app.session().save(&path)
+    {
+        eprintln!("Failed to save session to {}: {e}", path.display());
+    }
+
+    Ok(())
+}
+
+/// Undoes the terminal setup done before entering the TUI loop.
+fn restore_terminal() -> io::Result<()> {
     disable_raw_mode()?;
     execute!(
-        terminal.backend_mut(),
+        io::stdout(),
         LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )
+}
+
+/// Loads the mempool at `path`, or from stdin if `path` is `-`, showing
+/// progress on stderr for large dumps.
+fn load(path: &Path) -> Result<MempoolSerde, Box<dyn std::error::Error>> {
+    let mut shown = false;
+    let progress = |decoded, total| {
+        if decoded % 1_000 == 0 || (shown && decoded == total) {
+            eprint!(
+                "\rLoading {}: {decoded}/{total} transactions",
+                path.display()
+            );
+            shown = true;
+        }
+    };
+    let mempool = if is_stdio(path) {
+        MempoolSerde::from_reader_with_progress(io::stdin().lock(), progress)?
+    } else {
+        MempoolSerde::new_with_progress(path, progress)?
+    };
+    if shown {
+        eprintln!();
+    }
+    Ok(mempool)
+}
+
+/// Runs the operations given on the command line: the summary is printed,
+/// edits are applied and saved, then any export is written. With `--dry-run`
+/// the edits are reported instead and nothing is written.
+fn run_non_interactive(
+    mut mempool: MempoolSerde,
+    args: &Args,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.json {
+        println!("{:#}", Summary::compute(&mempool).to_json());
+        return Ok(());
+    }
+
+    if args.summary {
+        Summary::compute(&mempool).print();
+    }
+
+    if args.dry_run {
+        let before = mempool.clone();
+        apply_edits(&mut mempool, args)?;
+        convert_version(&mut mempool, args)?;
+        print_dry_run(&before, &mempool, args)?;
+        return Ok(());
+    }
+
+    if args.has_edits() || args.version_convert.is_some() {
+        let output = args
+            .output_path()
+            .ok_or("--output or --in-place is required to save edits")?;
+        if args.has_edits() {
+            apply_edits(&mut mempool, args)?;
+        }
+        convert_version(&mut mempool, args)?;
+        if is_stdio(output) {
+            let mut stdout = io::stdout().lock();
+            mempool.write_to(&mut stdout)?;
+            stdout.flush()?;
+        } else {
+            mempool.write_to_file(output)?;
+        }
+    }
+
+    if let Some(path) = &args.export_csv {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+        mempool.write_csv(io::BufWriter::new(file))?;
+        args.report(&format!(
+            "Exported {} transaction(s) to {}",
+            mempool.txs.len(),
+            path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reports what saving `after` would write and how it differs from `before`.
+fn print_dry_run(
+    before: &MempoolSerde,
+    after: &MempoolSerde,
+    args: &Args,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let before_txids: HashSet<Txid> = before.iter_with_txid().map(|(txid, _)| txid).collect();
+    let after_txids: HashSet<Txid> = after.iter_with_txid().map(|(txid, _)| txid).collect();
+    let changed_deltas = before
+        .map_deltas
+        .keys()
+        .chain(after.map_deltas.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter(|&txid| before.map_deltas.get(txid) != after.map_deltas.get(txid))
+        .count();
+
+    println!(
+        "Transactions:     {} -> {}",
+        before.txs.len(),
+        after.txs.len()
+    );
+    println!(
+        "Encoded size:     {} -> {} bytes",
+        before.to_bytes()?.len(),
+        after.to_bytes()?.len()
+    );
+    for (label, sign, from, to) in [
+        ("Removed:", '-', before, &after_txids),
+        ("Added:", '+', after, &before_txids),
+    ] {
+        let txids: Vec<Txid> = from
+            .iter_with_txid()
+            .map(|(txid, _)| txid)
+            .filter(|txid| !to.contains(txid))
+            .collect();
+        println!("{label:<18}{}", txids.len());
+        for txid in txids {
+            println!("  {sign} {txid}");
+        }
+    }
+    println!("Fee deltas:       {changed_deltas} changed");
+    println!(
+        "Unbroadcast:      {} -> {}",
+        before.unbroadcast_txids.len(),
+        after.unbroadcast_txids.len()
+    );
+
+    match args.output_path() {
+        Some(path) => println!("Dry run: {} was not written", path.display()),
+        None => println!("Dry run: nothing was written"),
+    }
+    Ok(())
+}
+
+/// Figures reported by `--summary` and `--json`.
+struct Summary {
+    version: u64,
+    compatibility: CoreCompat,
+    tx_count: usize,
+    total_vsize: usize,
+    total_weight: u64,
+    deltas_count: usize,
+    unbroadcast_count: usize,
+    fee_delta_total: i128,
+    /// `None` if the outputs overflow an amount
+    total_output_value: Option<Amount>,
+    /// Smallest, largest and mean fee delta, and the oldest and newest entry
+    /// times; `None` for an empty dump
+    ranges: Option<SummaryRanges>,
+}
+
+struct SummaryRanges {
+    fee_delta_min: i64,
+    fee_delta_max: i64,
+    fee_delta_mean: i128,
+    oldest: i64,
+    newest: i64,
+}
+
+impl Summary {
+    fn compute(mempool: &MempoolSerde) -> Self {
+        let txs = &mempool.txs;
+        let fee_delta_total: i128 = txs.iter().map(|txn| i128::from(txn.fee_delta)).sum();
+        let ranges = match (
+            txs.iter().map(|txn| txn.fee_delta).min(),
+            mempool.highest_fee_delta().map(|txn| txn.fee_delta),
+            mempool.oldest().map(|txn| txn.time),
+            mempool.newest().map(|txn| txn.time),
+        ) {
+            (Some(fee_delta_min), Some(fee_delta_max), Some(oldest), Some(newest)) => {
+                Some(SummaryRanges {
+                    fee_delta_min,
+                    fee_delta_max,
+                    fee_delta_mean: fee_delta_total
+                        / i128::try_from(txs.len()).unwrap_or(i128::MAX),
+                    oldest,
+                    newest,
+                })
+            }
+            _ => None,
+        };
+
+        Self {
+            version: mempool.version,
+            compatibility: mempool.core_compatibility(),
+            tx_count: txs.len(),
+            total_vsize: mempool.total_vsize(),
+            total_weight: mempool.total_weight().to_wu(),
+            deltas_count: mempool.map_deltas.len(),
+            unbroadcast_count: mempool.unbroadcast_txids.len(),
+            fee_delta_total,
+            total_output_value: mempool.total_output_value().ok(),
+            ranges,
+        }
+    }
+
+    /// Prints a human-readable report.
+    fn print(&self) {
+        println!("Version:          {}", self.version);
+        println!("Compatibility:    {}", self.compatibility);
+        println!("Transactions:     {}", self.tx_count);
+        println!("Total vsize:      {} vB", self.total_vsize);
+        println!("Total weight:     {} WU", self.total_weight);
+        match self.total_output_value {
+            Some(value) => println!("Output value:     {value}"),
+            None => println!("Output value:     overflows"),
+        }
+        println!("Fee deltas:       {}", self.deltas_count);
+        println!("Unbroadcast:      {}", self.unbroadcast_count);
+
+        if let Some(ranges) = &self.ranges {
+            println!("Fee delta total:  {} sat", self.fee_delta_total);
+            println!(
+                "Fee delta range:  {} to {} sat (mean {})",
+                ranges.fee_delta_min, ranges.fee_delta_max, ranges.fee_delta_mean
+            );
+            println!("Oldest entry:     {}", format_time(ranges.oldest));
+            println!("Newest entry:     {}", format_time(ranges.newest));
+        }
+    }
+
+    /// The summary as a JSON object. Range fields are `null` for an empty
+    /// dump, as is a fee delta total too large for a JSON number and an
+    /// output value that overflows.
+    fn to_json(&self) -> Value {
+        let ranges = self.ranges.as_ref();
+        json!({
+            "version": self.version,
+            "count": self.tx_count,
+            "total_vsize": self.total_vsize,
+            "total_weight": self.total_weight,
+            "total_output_value": self.total_output_value.map(Amount::to_sat),
+            "fee_delta": {
+                "total": Number::from_i128(self.fee_delta_total),
+                "min": ranges.map(|r| r.fee_delta_min),
+                "max": ranges.map(|r| r.fee_delta_max),
+                "mean": ranges.and_then(|r| Number::from_i128(r.fee_delta_mean)),
+            },
+            "deltas_count": self.deltas_count,
+            "unbroadcast_count": self.unbroadcast_count,
+            "oldest_time": ranges.map(|r| r.oldest),
+            "newest_time": ranges.map(|r| r.newest),
+        })
+    }
+}
+
+/// Applies `--version-convert`, printing a generated XOR key to stderr so
+/// the dump can still be read by hand.
+fn convert_version(mempool: &mut MempoolSerde, args: &Args) -> Result<(), &'static str> {
+    let Some(version) = args.version_convert else {
+        return Ok(());
+    };
+    match version {
+        DumpVersion::V1 => {
+            if args.xor_key.is_some() {
+                return Err("--xor-key only applies to --version-convert v2");
+            }
+            eprintln!(
+                "Warning: V1 dumps are not obfuscated, so raw transaction data is \
+                 stored in the clear"
+            );
+            mempool.downgrade_to_v1();
+        }
+        DumpVersion::V2 => {
+            let xor_key = args.xor_key.unwrap_or_else(|| {
+                let key = random_xor_key();
+                eprintln!("Generated XOR key {}", hex::encode(key));
+                key
+            });
+            mempool.upgrade_to_v2(xor_key);
+        }
+    }
+    args.report(&format!(
+        "Converted to version {} ({})",
+        mempool.version,
+        mempool.core_compatibility()
+    ));
+    Ok(())
+}
+
+/// A fresh XOR key. The key only obfuscates the dump, as Bitcoin Core's does
+/// to keep antivirus scanners from matching transaction data, so std's
+/// randomly seeded hasher is a sufficient source.
+fn random_xor_key() -> [u8; XOR_KEY_SIZE] {
+    use std::hash::{BuildHasher, Hasher};
+
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+        .to_le_bytes()
+}
+
+/// Applies `--delete` and then the insert options to `mempool`.
+fn apply_edits(mempool: &mut MempoolSerde, args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let removed = args
+        .delete
+        .iter()
+        .filter(|txid| mempool.remove_transaction(txid).is_some())
+        .count();
+    args.report(&format!("Removed {removed} transaction(s)"));
+
+    let mut hexes = args.insert_hex.clone();
+    for path in &args.insert_file {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        hexes.extend(tx_hex_lines(&contents).map(str::to_string));
+    }
+
+    // Decode everything up front so a bad transaction leaves the output untouched.
+    let txs = hexes
+        .iter()
+        .map(|hex| decode_tx_hex(hex))
+        .collect::<Result<Vec<_>, _>>()?;
+    let now = chrono::Utc::now().timestamp();
+    let inserted = txs.len();
+    for tx in txs {
+        mempool.add_transaction(tx, now, 0)?;
+    }
+    args.report(&format!("Inserted {inserted} transaction(s)"));
 
     Ok(())
 }
@@ -226,9 +1865,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 #[allow(clippy::cast_possible_truncation)]
 fn ui(f: &mut Frame, app: &mut App) {
     let size = f.area();
+    let theme = app.theme;
 
     // Animated background effect
-    let bg_color = Color::Rgb(0, (10 + (app.animation_tick % 20)) as u8, 0);
+    let bg_color = if app.animate {
+        Color::Rgb(0, (10 + (app.animation_tick % 20)) as u8, 0)
+    } else {
+        theme.background
+    };
 
     let main_block = Block::default().style(Style::default().bg(bg_color));
     f.render_widget(main_block, size);
@@ -245,20 +1889,66 @@ fn ui(f: &mut Frame, app: &mut App) {
     // Header with animated title
     let glow = ((app.animation_tick % 30) as u8).saturating_mul(8);
     let title_style = Style::default()
-        .fg(Color::Rgb(0, 255, glow.saturating_add(100)))
+        .fg(if app.animate {
+            Color::Rgb(0, 255, glow.saturating_add(100))
+        } else {
+            theme.title
+        })
         .add_modifier(Modifier::BOLD);
 
+    let txids = app.txids();
+    let package = app.package();
+
+    let compat = app.mempool.core_compatibility();
+    let summary = app.stats();
+    let summary_text = format!(
+        "  │  {} txs · {} vB · Δ {} sat · {} unbroadcast  │  {}",
+        summary.tx_count,
+        summary.total_vsize,
+        summary.total_fee_delta,
+        summary.unbroadcast_count,
+        compat
+    );
+    let duplicates = summary.duplicates.clone();
+    let duplicates_text = if duplicates.is_empty() {
+        String::new()
+    } else {
+        format!("  │  {} duplicated", duplicates.len())
+    };
+
+    // Mainnet stands out in red so it is not mistaken for a test network.
+    let network_badge = match app.network {
+        Some(Network::Bitcoin) => Span::styled(
+            " MAINNET ",
+            Style::default()
+                .bg(theme.mainnet_badge)
+                .fg(theme.badge_fg)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Some(network) => Span::styled(
+            format!(" {} ", network.to_string().to_uppercase()),
+            Style::default()
+                .bg(theme.network_badge)
+                .fg(theme.badge_dark_fg)
+                .add_modifier(Modifier::BOLD),
+        ),
+        None => Span::styled(" NETWORK ? ", Style::default().fg(theme.muted)),
+    };
+
     let header = Paragraph::new(Line::from(vec![
-        Span::styled("◆ ", Style::default().fg(Color::Rgb(0, 255, 100))),
+        Span::styled("◆ ", Style::default().fg(theme.title)),
         Span::styled("WINDFISH", title_style),
-        Span::styled(" ◆ ", Style::default().fg(Color::Rgb(0, 255, 100))),
-        Span::styled("Mempool Editor", Style::default().fg(Color::DarkGray)),
+        Span::styled(" ◆ ", Style::default().fg(theme.title)),
+        Span::styled("Mempool Editor ", Style::default().fg(theme.muted)),
+        network_badge,
+        Span::styled(summary_text, Style::default().fg(theme.label)),
+        Span::styled(duplicates_text, Style::default().fg(theme.danger)),
     ]))
     .block(
         Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Rgb(0, 180, 0)))
-            .style(Style::default().bg(Color::Rgb(0, 20, 0))),
+            .border_style(Style::default().fg(theme.header_border))
+            .style(Style::default().bg(theme.header_bg)),
     );
     f.render_widget(header, chunks[0]);
 
@@ -270,29 +1960,38 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     // Left panel - TX list
     let items: Vec<ListItem> = app
-        .mempool
-        .txs
+        .view
         .iter()
         .enumerate()
-        .map(|(i, txn)| {
-            let txid = txn.tx.compute_txid().to_string();
-            let short_txid = format!("{}...{}", &txid[..8], &txid[txid.len() - 8..]);
+        .map(|(pos, &i)| {
+            let txid = txids[i];
+            let marker = if app.mempool.unbroadcast_txids.contains(&txid) {
+                "● "
+            } else {
+                "  "
+            };
+            let rbf = if windfish::is_rbf_signaling(&app.mempool.txs[i].tx) {
+                "↻ "
+            } else {
+                "  "
+            };
 
-            let style = if Some(i) == app.list_state.selected() {
+            let style = if Some(pos) == app.list_state.selected() {
                 Style::default()
-                    .fg(Color::Rgb(0, 255, 0))
-                    .bg(Color::Rgb(0, 50, 0))
+                    .fg(theme.item_selected)
+                    .bg(theme.selected_bg)
                     .add_modifier(Modifier::BOLD)
+            } else if duplicates.contains_key(&txid) {
+                Style::default().fg(theme.danger)
             } else {
-                Style::default().fg(Color::Rgb(0, 200, 0))
+                Style::default().fg(theme.item)
             };
 
             ListItem::new(Line::from(vec![
-                Span::styled(
-                    format!("{:3} ", i + 1),
-                    Style::default().fg(Color::DarkGray),
-                ),
-                Span::styled(short_txid, style),
+                Span::styled(format!("{:3} ", i + 1), Style::default().fg(theme.muted)),
+                Span::styled(marker, Style::default().fg(theme.accent)),
+                Span::styled(rbf, Style::default().fg(theme.fee)),
+                Span::styled(short_txid(&txid), style),
             ]))
         })
         .collect();
@@ -301,118 +2000,201 @@ fn ui(f: &mut Frame, app: &mut App) {
         .block(
             Block::default()
                 .title(Span::styled(
-                    format!(" TXIDs ({}) ", app.mempool.txs.len()),
+                    list_title(app),
                     Style::default()
-                        .fg(Color::Rgb(0, 255, 100))
+                        .fg(theme.title)
                         .add_modifier(Modifier::BOLD),
                 ))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Rgb(0, 120, 0)))
-                .style(Style::default().bg(Color::Rgb(0, 15, 0))),
+                .border_style(Style::default().fg(pane_border(app, Focus::List)))
+                .border_type(pane_border_type(app, Focus::List))
+                .style(Style::default().bg(theme.panel_bg)),
         )
         .highlight_symbol("▶ ")
         .highlight_style(Style::default().add_modifier(Modifier::BOLD));
 
+    // Rows inside the list's borders, for paging
+    app.page_size = usize::from(content_chunks[0].height.saturating_sub(2)).max(1);
+    app.list_area = content_chunks[0];
     f.render_stateful_widget(list, content_chunks[0], &mut app.list_state);
 
     // Right panel - TX details
-    let details = app.selected_tx().map_or_else(
+    let details = app.selected_index().map_or_else(
         || {
             vec![Line::from(Span::styled(
                 "No transaction selected",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme.muted),
             ))]
         },
-        |txn| {
-            let txid = txn.tx.compute_txid();
-            let datetime = chrono::DateTime::from_timestamp(txn.time, 0).map_or_else(
-                || "Unknown".to_string(),
-                |dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
-            );
+        |i| {
+            let txn = &app.mempool.txs[i];
+            let txid = txids[i];
+            let datetime = format_time(txn.time);
+            let (ancestors, descendants) = package.unwrap_or_default();
+            // Parents among the dump's transactions, found in one pass over
+            // `txids`.
+            let spent: HashSet<Txid> = txn
+                .tx
+                .input
+                .iter()
+                .map(|txin| txin.previous_output.txid)
+                .collect();
+            let in_dump: HashSet<Txid> = txids
+                .iter()
+                .copied()
+                .filter(|txid| spent.contains(txid))
+                .collect();
 
-            vec![
+            let mut lines: Vec<Line> = vec![
+                Line::from(vec![
+                    Span::styled("TXID: ", Style::default().fg(theme.label)),
+                    Span::styled(txid.to_string(), Style::default().fg(theme.title)),
+                ]),
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled("Version: ", Style::default().fg(theme.label)),
+                    Span::styled(txn.tx.version.to_string(), Style::default().fg(theme.text)),
+                ]),
+                Line::from(vec![
+                    Span::styled("Lock Time: ", Style::default().fg(theme.label)),
+                    Span::styled(
+                        txn.tx.lock_time.to_string(),
+                        Style::default().fg(theme.text),
+                    ),
+                ]),
                 Line::from(vec![
-                    Span::styled("TXID: ", Style::default().fg(Color::Rgb(0, 150, 0))),
+                    Span::styled("Replaceable: ", Style::default().fg(theme.label)),
                     Span::styled(
-                        txid.to_string(),
-                        Style::default().fg(Color::Rgb(0, 255, 100)),
+                        if windfish::is_rbf_signaling(&txn.tx) {
+                            "yes (signals BIP125)"
+                        } else {
+                            "no signal"
+                        },
+                        Style::default().fg(theme.text),
                     ),
                 ]),
-                Line::from(""),
                 Line::from(vec![
-                    Span::styled("Version: ", Style::default().fg(Color::Rgb(0, 150, 0))),
+                    Span::styled("Inputs: ", Style::default().fg(theme.label)),
                     Span::styled(
-                        txn.tx.version.to_string(),
-                        Style::default().fg(Color::White),
+                        txn.tx.input.len().to_string(),
+                        Style::default().fg(theme.number),
                     ),
                 ]),
                 Line::from(vec![
-                    Span::styled("Lock Time: ", Style::default().fg(Color::Rgb(0, 150, 0))),
+                    Span::styled("Outputs: ", Style::default().fg(theme.label)),
                     Span::styled(
-                        txn.tx.lock_time.to_string(),
-                        Style::default().fg(Color::White),
+                        txn.tx.output.len().to_string(),
+                        Style::default().fg(theme.number),
                     ),
                 ]),
                 Line::from(vec![
-                    Span::styled("Inputs: ", Style::default().fg(Color::Rgb(0, 150, 0))),
+                    Span::styled("Size: ", Style::default().fg(theme.label)),
                     Span::styled(
-                        txn.tx.input.len().to_string(),
-                        Style::default().fg(Color::Cyan),
+                        format!("{} vB ({} WU)", txn.vsize(), txn.weight().to_wu()),
+                        Style::default().fg(theme.number),
                     ),
                 ]),
                 Line::from(vec![
-                    Span::styled("Outputs: ", Style::default().fg(Color::Rgb(0, 150, 0))),
+                    Span::styled("Package: ", Style::default().fg(theme.label)),
                     Span::styled(
-                        txn.tx.output.len().to_string(),
-                        Style::default().fg(Color::Cyan),
+                        format!("{ancestors} ancestor(s), {descendants} descendant(s)"),
+                        Style::default().fg(theme.number),
                     ),
                 ]),
                 Line::from(""),
                 Line::from(vec![
-                    Span::styled("Time: ", Style::default().fg(Color::Rgb(0, 150, 0))),
-                    Span::styled(datetime, Style::default().fg(Color::Yellow)),
+                    Span::styled("Time: ", Style::default().fg(theme.label)),
+                    Span::styled(datetime, Style::default().fg(theme.time)),
                 ]),
                 Line::from(vec![
-                    Span::styled("Fee Delta: ", Style::default().fg(Color::Rgb(0, 150, 0))),
+                    Span::styled("Fee Delta: ", Style::default().fg(theme.label)),
                     Span::styled(
                         format!("{} sat", txn.fee_delta),
-                        Style::default().fg(Color::Magenta),
+                        Style::default().fg(theme.fee),
                     ),
                 ]),
                 Line::from(""),
                 Line::from(Span::styled(
-                    "─── Outputs ───",
-                    Style::default().fg(Color::Rgb(0, 100, 0)),
+                    "─── Inputs ───",
+                    Style::default().fg(theme.rule),
                 )),
             ]
             .into_iter()
+            .chain(txn.tx.input.iter().enumerate().map(|(i, txin)| {
+                let prevout = txin.previous_output;
+                let mut spans = vec![
+                    Span::styled(format!("  [{i}] "), Style::default().fg(theme.muted)),
+                    Span::styled(
+                        format!("{}:{}", short_txid(&prevout.txid), prevout.vout),
+                        Style::default().fg(theme.text),
+                    ),
+                    Span::styled(
+                        format!(" seq {:#010x}", txin.sequence.0),
+                        Style::default().fg(theme.number),
+                    ),
+                ];
+                if in_dump.contains(&prevout.txid) {
+                    spans.push(Span::styled(
+                        " (in dump)",
+                        Style::default().fg(theme.accent),
+                    ));
+                }
+                Line::from(spans)
+            }))
+            .chain([
+                Line::from(""),
+                Line::from(Span::styled(
+                    "─── Outputs ───",
+                    Style::default().fg(theme.rule),
+                )),
+            ])
             .chain(txn.tx.output.iter().enumerate().map(|(i, out)| {
                 Line::from(vec![
-                    Span::styled(format!("  [{i}] "), Style::default().fg(Color::DarkGray)),
+                    Span::styled(format!("  [{i}] "), Style::default().fg(theme.muted)),
                     Span::styled(
                         format!("{} sat", out.value.to_sat()),
-                        Style::default().fg(Color::Rgb(255, 200, 0)),
+                        Style::default().fg(theme.amount),
                     ),
                 ])
             }))
-            .collect()
+            .collect();
+            if let Some(copies) = duplicates.get(&txid) {
+                let numbers: Vec<String> = copies
+                    .iter()
+                    .filter(|&&copy| copy != i)
+                    .map(|copy| (copy + 1).to_string())
+                    .collect();
+                lines.insert(
+                    1,
+                    Line::from(Span::styled(
+                        format!("Duplicate: also at {}", numbers.join(", ")),
+                        Style::default().fg(theme.danger),
+                    )),
+                );
+            }
+            lines
         },
     );
 
+    let last_line = u16::try_from(details.len().saturating_sub(1)).unwrap_or(u16::MAX);
+    let details_scroll = app.details_scroll().min(last_line);
+    app.details_scroll = (app.selected_txid(), details_scroll);
     let details_widget = Paragraph::new(details)
         .block(
             Block::default()
                 .title(Span::styled(
                     " Details ",
                     Style::default()
-                        .fg(Color::Rgb(0, 255, 100))
+                        .fg(theme.title)
                         .add_modifier(Modifier::BOLD),
                 ))
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Rgb(0, 120, 0)))
-                .style(Style::default().bg(Color::Rgb(0, 15, 0))),
+                .border_style(Style::default().fg(pane_border(app, Focus::Details)))
+                .border_type(pane_border_type(app, Focus::Details))
+                .style(Style::default().bg(theme.panel_bg)),
         )
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: true })
+        .scroll((details_scroll, 0));
 
     f.render_widget(details_widget, content_chunks[1]);
 
@@ -420,70 +2202,475 @@ fn ui(f: &mut Frame, app: &mut App) {
     let mode_indicator = match app.mode {
         Mode::Normal => Span::styled(
             " NORMAL ",
-            Style::default().bg(Color::Rgb(0, 100, 0)).fg(Color::White),
+            Style::default().bg(theme.normal_badge).fg(theme.badge_fg),
         ),
-        Mode::Insert => Span::styled(
+        Mode::Insert | Mode::ImportFile => Span::styled(
             " INSERT ",
             Style::default()
-                .bg(Color::Rgb(100, 100, 0))
-                .fg(Color::Black),
+                .bg(theme.insert_badge)
+                .fg(theme.badge_dark_fg),
+        ),
+        Mode::ExportTx => Span::styled(
+            " EXPORT ",
+            Style::default().bg(theme.view_badge).fg(theme.badge_fg),
+        ),
+        Mode::Filter => Span::styled(
+            " FILTER ",
+            Style::default().bg(theme.filter_badge).fg(theme.badge_fg),
+        ),
+        Mode::Command => Span::styled(
+            " COMMAND ",
+            Style::default().bg(theme.filter_badge).fg(theme.badge_fg),
+        ),
+        Mode::Unbroadcast => Span::styled(
+            " UNBROADCAST ",
+            Style::default().bg(theme.view_badge).fg(theme.badge_fg),
+        ),
+        Mode::RawHex => Span::styled(
+            " HEX ",
+            Style::default().bg(theme.view_badge).fg(theme.badge_fg),
+        ),
+        Mode::Help => Span::styled(
+            " HELP ",
+            Style::default().bg(theme.view_badge).fg(theme.badge_fg),
+        ),
+        Mode::ConfirmDelete => Span::styled(
+            " DELETE ",
+            Style::default().bg(theme.delete_badge).fg(theme.badge_fg),
+        ),
+        Mode::ConfirmQuit => Span::styled(
+            " QUIT ",
+            Style::default().bg(theme.delete_badge).fg(theme.badge_fg),
+        ),
+        Mode::EditFeeDelta | Mode::EditTime => Span::styled(
+            " EDIT ",
+            Style::default().bg(theme.edit_badge).fg(theme.badge_fg),
         ),
     };
 
     let help_text = match app.mode {
-        Mode::Normal => "q:quit  ↑↓/jk:nav  i:insert  d:delete  s:save",
-        Mode::Insert => "Enter:confirm  Esc:cancel  (paste raw tx hex)",
+        Mode::Normal => {
+            "?:help  q:quit  Tab:focus  [count]↑↓/jk:nav  J/K:scroll details  PgUp/PgDn:page  gg/G:top/end  :N:jump  /:filter  a:anomalies  o:sort  y:copy  x:hex  u/U:unbroadcast  i:insert  I:import  e:export  [count]d:delete  f:fee  F:clear fees  t:time  s:save"
+                .to_string()
+        }
+        Mode::Insert => "Enter:confirm  Esc:cancel  (paste raw tx hex)".to_string(),
+        Mode::ImportFile => {
+            "Enter:import  Esc:cancel  (file with one raw tx hex per line)".to_string()
+        }
+        Mode::ExportTx => {
+            "Enter:export  Esc:cancel  (hex, or raw bytes if the path ends in .bin)".to_string()
+        }
+        Mode::Filter => format!("/{}  Enter:keep  Esc:clear  (type txid prefix)", app.filter),
+        Mode::Command => format!(
+            ":{}  Enter:run  Esc:cancel  (N, txid <hex>, addr <address> or version 1|2 [key])",
+            app.input_buffer
+        ),
+        Mode::EditFeeDelta => "Enter:confirm  Esc:cancel  (signed sats)".to_string(),
+        Mode::ConfirmDelete => "y:delete  n/Esc:cancel".to_string(),
+        Mode::ConfirmQuit => "y:quit  s:save and quit  n/Esc:cancel".to_string(),
+        Mode::RawHex => "↑↓/jk:scroll  x/Esc:close".to_string(),
+        Mode::Help => "↑↓/jk:scroll  ?/Esc:close".to_string(),
+        Mode::Unbroadcast => "↑↓/jk:nav  d:remove  D:clear all  U/Esc:close".to_string(),
+        Mode::EditTime => {
+            "Enter:confirm  Esc:cancel  (unix seconds or YYYY-MM-DD HH:MM:SS)".to_string()
+        }
     };
 
     let status = if let Some((msg, _)) = &app.status_message {
-        Span::styled(
-            format!(" {msg} "),
-            Style::default().fg(Color::Rgb(255, 255, 0)),
-        )
+        Span::styled(format!(" {msg} "), Style::default().fg(theme.status))
     } else {
         Span::styled("", Style::default())
     };
 
+    let count = app
+        .pending_count
+        .filter(|_| app.mode == Mode::Normal)
+        .map_or_else(String::new, |count| format!(" {count}"));
+
     let footer = Paragraph::new(Line::from(vec![
         mode_indicator,
+        Span::styled(count, Style::default().fg(theme.status)),
         Span::raw(" "),
-        Span::styled(help_text, Style::default().fg(Color::Rgb(0, 150, 0))),
+        Span::styled(help_text, Style::default().fg(theme.label)),
         Span::raw("  "),
         status,
     ]))
     .block(
         Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Rgb(0, 80, 0)))
-            .style(Style::default().bg(Color::Rgb(0, 10, 0))),
+            .border_style(Style::default().fg(theme.footer_border))
+            .style(Style::default().bg(theme.footer_bg)),
     );
 
     f.render_widget(footer, chunks[2]);
 
-    // Insert mode popup
-    if app.mode == Mode::Insert {
+    // Input popup
+    let popup_title = match app.mode {
+        Mode::Insert => Some(" Insert Raw Transaction (hex) "),
+        Mode::ImportFile => Some(" Import Transactions From File (path) "),
+        Mode::ExportTx => Some(" Export Transaction To File (path) "),
+        Mode::EditFeeDelta => Some(" Edit Fee Delta (sat) "),
+        Mode::EditTime => Some(" Edit Time (UTC) "),
+        Mode::Normal
+        | Mode::Filter
+        | Mode::Command
+        | Mode::ConfirmDelete
+        | Mode::ConfirmQuit
+        | Mode::RawHex
+        | Mode::Unbroadcast
+        | Mode::Help => None,
+    };
+    if let Some(title) = popup_title {
         let popup_area = centered_rect(70, 20, size);
         f.render_widget(Clear, popup_area);
 
+        let mut block = Block::default()
+            .title(Span::styled(
+                title,
+                Style::default()
+                    .fg(theme.status)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.popup_border))
+            .style(Style::default().bg(theme.popup_bg));
+        if let Some(error) = &app.input_error {
+            block = block
+                .title_bottom(Span::styled(
+                    format!(" {error} "),
+                    Style::default().fg(theme.danger),
+                ))
+                .border_style(Style::default().fg(theme.danger_border));
+        }
+
         let input = Paragraph::new(app.input_buffer.as_str())
+            .block(block)
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(input, popup_area);
+    }
+
+    // Raw transaction hex popup
+    if app.mode == Mode::RawHex
+        && let Some(i) = app.selected_index()
+    {
+        let txn = &app.mempool.txs[i];
+        let popup_area = centered_rect(80, 60, size);
+        f.render_widget(Clear, popup_area);
+
+        let raw_hex = Paragraph::new(serialize_hex(&txn.tx))
+            .style(Style::default().fg(theme.hex))
             .block(
                 Block::default()
                     .title(Span::styled(
-                        " Insert Raw Transaction (hex) ",
+                        format!(" Raw Transaction {} ", short_txid(&txids[i])),
                         Style::default()
-                            .fg(Color::Rgb(255, 255, 0))
+                            .fg(theme.accent)
                             .add_modifier(Modifier::BOLD),
                     ))
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(Color::Rgb(200, 200, 0)))
-                    .style(Style::default().bg(Color::Rgb(20, 20, 0))),
+                    .border_style(Style::default().fg(theme.hex_border))
+                    .style(Style::default().bg(theme.hex_bg)),
             )
-            .wrap(Wrap { trim: false });
+            .wrap(Wrap { trim: false })
+            .scroll((app.hex_scroll, 0));
 
-        f.render_widget(input, popup_area);
+        f.render_widget(raw_hex, popup_area);
+    }
+
+    // Keybinding help popup
+    if app.mode == Mode::Help {
+        let popup_area = centered_rect(70, 80, size);
+        f.render_widget(Clear, popup_area);
+
+        let lines: Vec<Line> = KEYBINDINGS
+            .iter()
+            .enumerate()
+            .flat_map(|(n, (group, bindings))| {
+                let heading = Line::from(Span::styled(
+                    *group,
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ));
+                (n > 0)
+                    .then(|| Line::from(""))
+                    .into_iter()
+                    .chain(std::iter::once(heading))
+                    .chain(bindings.iter().map(|(keys, action)| {
+                        Line::from(vec![
+                            Span::styled(format!("  {keys:<16}"), Style::default().fg(theme.hex)),
+                            Span::styled(*action, Style::default().fg(theme.text)),
+                        ])
+                    }))
+            })
+            .collect();
+        let last_line = u16::try_from(lines.len().saturating_sub(1)).unwrap_or(u16::MAX);
+        app.help_scroll = app.help_scroll.min(last_line);
+
+        let help = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(Span::styled(
+                        " Keybindings ",
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.popup_border))
+                    .style(Style::default().bg(theme.popup_bg)),
+            )
+            .scroll((app.help_scroll, 0));
+        f.render_widget(help, popup_area);
+    }
+
+    // Unbroadcast TXID popup
+    if app.mode == Mode::Unbroadcast {
+        let popup_area = centered_rect(80, 60, size);
+        f.render_widget(Clear, popup_area);
+
+        let in_mempool: HashSet<Txid> = txids.iter().copied().collect();
+        let items: Vec<ListItem> = app
+            .unbroadcast_list()
+            .iter()
+            .map(|txid| {
+                let mut spans = vec![Span::styled(
+                    txid.to_string(),
+                    Style::default().fg(theme.hex),
+                )];
+                if !in_mempool.contains(txid) {
+                    spans.push(Span::styled(
+                        "  (not in mempool)",
+                        Style::default().fg(theme.muted),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(Span::styled(
+                        format!(
+                            " Unbroadcast TXIDs ({}) ",
+                            app.mempool.unbroadcast_txids.len()
+                        ),
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.hex_border))
+                    .style(Style::default().bg(theme.hex_bg)),
+            )
+            .highlight_symbol("▶ ")
+            .highlight_style(
+                Style::default()
+                    .bg(theme.hex_selected_bg)
+                    .add_modifier(Modifier::BOLD),
+            );
+
+        f.render_stateful_widget(list, popup_area, &mut app.unbroadcast_state);
+    }
+
+    // Delete confirmation popup
+    if app.mode == Mode::ConfirmDelete
+        && let Some(i) = app.selected_index()
+    {
+        let popup_area = centered_rect(40, 20, size);
+        f.render_widget(Clear, popup_area);
+
+        let confirm = Paragraph::new(vec![
+            Line::from(""),
+            Line::from(vec![
+                Span::styled(
+                    match app.pending_count {
+                        Some(count) if count > 1 => format!("Delete {count} transactions from "),
+                        _ => "Delete ".to_string(),
+                    },
+                    Style::default().fg(theme.text),
+                ),
+                Span::styled(
+                    short_txid(&txids[i]),
+                    Style::default()
+                        .fg(theme.danger)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("?", Style::default().fg(theme.text)),
+            ]),
+            Line::from(""),
+            Line::from(Span::styled(
+                "y: delete   n: cancel",
+                Style::default().fg(theme.muted),
+            )),
+        ])
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    " Confirm Delete ",
+                    Style::default()
+                        .fg(theme.danger)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.danger_border))
+                .style(Style::default().bg(theme.danger_bg)),
+        );
+
+        f.render_widget(confirm, popup_area);
+    }
+
+    // Quit confirmation popup
+    if app.mode == Mode::ConfirmQuit {
+        let popup_area = centered_rect(40, 20, size);
+        f.render_widget(Clear, popup_area);
+
+        let confirm = Paragraph::new(vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                "Unsaved changes — quit anyway?",
+                Style::default().fg(theme.text),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "y: quit   n: cancel   s: save and quit",
+                Style::default().fg(theme.muted),
+            )),
+        ])
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    " Confirm Quit ",
+                    Style::default()
+                        .fg(theme.danger)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.danger_border))
+                .style(Style::default().bg(theme.danger_bg)),
+        );
+
+        f.render_widget(confirm, popup_area);
+    }
+}
+
+/// Border color of the list or details pane, picked out when it has focus.
+fn pane_border(app: &App, pane: Focus) -> Color {
+    if app.focus == pane {
+        app.theme.accent
+    } else {
+        app.theme.panel_border
+    }
+}
+
+/// Border line of the list or details pane, thick when it has focus so the
+/// focus shows even in themes that color both borders alike.
+fn pane_border_type(app: &App, pane: Focus) -> BorderType {
+    if app.focus == pane {
+        BorderType::Thick
+    } else {
+        BorderType::Plain
+    }
+}
+
+/// Title of the TXID list: counts, sort order and any active filters.
+fn list_title(app: &App) -> String {
+    let count = if app.filter.is_empty() && !app.anomalies_only && app.address_filter.is_none() {
+        format!("TXIDs ({})", app.mempool.txs.len())
+    } else {
+        format!("TXIDs ({}/{})", app.view.len(), app.mempool.txs.len())
+    };
+    let mut parts = vec![count, app.sort_mode.label().to_string()];
+    if app.anomalies_only {
+        parts.push("anomalies".to_string());
+    }
+    if let Some((address, _)) = &app.address_filter {
+        parts.push(format!("addr {address}"));
     }
+    if !app.filter.is_empty() {
+        parts.push(format!("/{}", app.filter));
+    }
+    format!(" {} ", parts.join(" · "))
 }
 
+/// Every keybinding, grouped by the mode it applies in, for the `?` popup.
+const KEYBINDINGS: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Transaction list",
+        &[
+            ("[count]↑↓ j k", "Move the selection"),
+            ("PgUp PgDn", "Move a page (also Ctrl-u Ctrl-d)"),
+            ("gg G Home End", "Jump to the top or end"),
+            ("J K", "Scroll the details panel"),
+            ("Tab", "Focus the details panel"),
+            ("/", "Filter by TXID prefix"),
+            (":", "Run a command"),
+            ("a", "Show only transactions with anomalies"),
+            ("o", "Cycle the sort order"),
+            ("y", "Copy the selected TXID"),
+            ("x", "Show the raw transaction hex"),
+            ("u", "Toggle unbroadcast for the selection"),
+            ("U", "List unbroadcast TXIDs"),
+            ("i", "Insert a raw transaction"),
+            ("I", "Import transactions from a file"),
+            ("e", "Export the selected transaction"),
+            ("[count]d", "Delete transactions"),
+            ("f", "Edit the fee delta"),
+            ("F", "Clear all fee deltas"),
+            ("t", "Edit the entry time"),
+            ("s", "Save the dump"),
+            ("?", "Show this help"),
+            ("q", "Quit, asking first if there are unsaved changes"),
+        ],
+    ),
+    (
+        "Details panel (after Tab)",
+        &[
+            ("[count]↑↓ j k", "Scroll"),
+            ("PgUp PgDn", "Scroll a page (also Ctrl-u Ctrl-d)"),
+            ("gg G Home End", "Scroll to the top or end"),
+            ("Tab", "Focus the transaction list"),
+        ],
+    ),
+    (
+        "Commands (:)",
+        &[
+            ("N", "Jump to transaction N"),
+            ("txid <hex>", "Jump to the first TXID with this prefix"),
+            ("addr <address>", "List only transactions paying an address"),
+            ("addr", "Clear the address filter"),
+            ("version 1", "Convert to an unobfuscated V1 dump"),
+            ("version 2 [key]", "Convert to a V2 dump with an XOR key"),
+        ],
+    ),
+    (
+        "Unbroadcast list (U)",
+        &[
+            ("↑↓ j k", "Move the selection"),
+            ("d", "Remove the selected TXID"),
+            ("D", "Remove every TXID"),
+            ("U Esc", "Close"),
+        ],
+    ),
+    (
+        "Raw hex (x) and help (?)",
+        &[("↑↓ j k", "Scroll"), ("x ? Esc", "Close")],
+    ),
+    (
+        "Prompts",
+        &[
+            ("Enter", "Confirm"),
+            ("Esc", "Cancel"),
+            ("y n", "Answer a delete or quit confirmation"),
+            ("s", "Save and quit, when asked to confirm quitting"),
+        ],
+    ),
+];
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -503,3 +2690,616 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn fixture() -> MempoolSerde {
+        MempoolSerde::new(Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test/mempool_t4_v1_001.dat"
+        )))
+        .unwrap()
+    }
+
+    fn app() -> App {
+        app_for(fixture())
+    }
+
+    fn app_for(mempool: MempoolSerde) -> App {
+        App::new(
+            mempool,
+            PathBuf::from("out.dat"),
+            None,
+            true,
+            ThemeName::Matrix.theme(),
+        )
+    }
+
+    /// Draws `app` on a `width` by `height` terminal and returns the screen's
+    /// text, row after row.
+    fn screen(app: &mut App, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(ratatui::buffer::Cell::symbol)
+            .collect()
+    }
+
+    /// Draws every mode of `app` in every theme, at a normal and a cramped
+    /// terminal size.
+    fn render_all_modes(mut app: App) {
+        for theme in ThemeName::value_variants() {
+            app.theme = theme.theme();
+            for mode in [
+                Mode::Normal,
+                Mode::Insert,
+                Mode::ImportFile,
+                Mode::ExportTx,
+                Mode::Filter,
+                Mode::EditFeeDelta,
+                Mode::EditTime,
+                Mode::ConfirmDelete,
+                Mode::ConfirmQuit,
+                Mode::RawHex,
+                Mode::Unbroadcast,
+                Mode::Command,
+                Mode::Help,
+            ] {
+                app.mode = mode;
+                for (width, height) in [(120, 40), (10, 4)] {
+                    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                    terminal.draw(|f| ui(f, &mut app)).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn renders_with_any_number_of_transactions() {
+        let full = fixture();
+        let one = MempoolSerde::empty(1)
+            .with_transaction(full.txs[0].tx.clone(), 0, 0)
+            .unwrap();
+
+        for mempool in [MempoolSerde::empty(2), one, full] {
+            render_all_modes(app_for(mempool.clone()));
+
+            let mut app = app_for(mempool);
+            // Navigation and edits on the selection must not panic either.
+            app.next();
+            app.previous();
+            app.page_down();
+            app.last();
+            app.delete_selected(1);
+            app.delete_selected(2);
+            app.toggle_unbroadcast();
+            app.open_unbroadcast();
+            app.remove_unbroadcast();
+            app.toggle_unbroadcast();
+            app.clear_unbroadcast();
+            app.clear_deltas();
+            render_all_modes(app);
+        }
+    }
+
+    #[test]
+    fn header_sums_fee_deltas_without_overflow() {
+        let mut mempool = fixture();
+        for txn in &mut mempool.txs[..2] {
+            txn.fee_delta = i64::MAX;
+        }
+        let mut app = app_for(mempool);
+        assert_eq!(app.stats().total_fee_delta, 2 * i128::from(i64::MAX));
+
+        assert!(screen(&mut app, 160, 40).contains(&format!("Δ {} sat", 2 * i128::from(i64::MAX))));
+    }
+
+    #[test]
+    fn load_decompresses_by_extension() {
+        let dir = std::env::temp_dir().join(format!("windfish-load-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mempool.dat.gz");
+        let mempool = fixture();
+
+        let written = mempool.write_to_file(&path);
+        if cfg!(feature = "gzip") {
+            written.unwrap();
+            assert_eq!(load(&path).unwrap(), mempool);
+        } else {
+            assert!(written.is_err());
+            std::fs::write(&path, mempool.to_bytes().unwrap()).unwrap();
+            assert!(load(&path).is_err());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn import_reports_the_first_failure_and_only_dirties_on_insert() {
+        let dir = std::env::temp_dir().join(format!("windfish-import-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("txs.txt");
+        let mut app = app();
+        app.output_path = dir.join("out.dat");
+        let count = app.mempool.txs.len();
+
+        std::fs::write(&path, "zz\n00\n").unwrap();
+        app.import_file(path.to_str().unwrap()).unwrap();
+        let (status, _) = app.status_message.clone().unwrap();
+        assert!(status.starts_with("Imported 0 transaction(s), 2 failed (first: Invalid hex"));
+        assert!(!app.dirty);
+
+        let mut tx = app.mempool.txs[0].tx.clone();
+        tx.lock_time = bitcoin::absolute::LockTime::from_consensus(1);
+        std::fs::write(&path, format!("{}\nzz\n", serialize_hex(&tx))).unwrap();
+        app.import_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(app.mempool.txs.len(), count + 1);
+        assert!(app.dirty);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn multi_line_pastes_insert_whole_transactions() {
+        let mut app = app();
+        let count = app.mempool.txs.len();
+        let mut tx = app.mempool.txs[0].tx.clone();
+        tx.lock_time = bitcoin::absolute::LockTime::from_consensus(1);
+        let hex = serialize_hex(&tx);
+        let wrapped: Vec<&str> = hex
+            .as_bytes()
+            .chunks(64)
+            .map(|line| std::str::from_utf8(line).unwrap())
+            .collect();
+
+        // Outside a text input, pastes are ignored.
+        app.paste(&wrapped.join("\n"));
+        assert!(app.input_buffer.is_empty());
+
+        app.mode = Mode::Insert;
+        app.paste(&format!("0x{}\r\n", wrapped.join("\r\n")));
+        let input = app.input_buffer.clone();
+        app.insert_tx(&input).unwrap();
+        assert_eq!(app.mempool.txs.len(), count + 1);
+        assert_eq!(app.mempool.txs[count].tx, tx);
+
+        app.mode = Mode::Filter;
+        app.paste("ab\ncd");
+        assert_eq!(app.filter, "abcd");
+    }
+
+    #[test]
+    fn mouse_selects_and_scrolls_the_list() {
+        let mut app = app();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+
+        let mouse = |kind, column, row| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        let click = MouseEventKind::Down(MouseButton::Left);
+        let rows = app.list_area.inner(Margin::new(1, 1));
+
+        app.handle_mouse(mouse(click, rows.x, rows.y + 3));
+        assert_eq!(app.list_state.selected(), Some(3));
+
+        // Clicks on the border or outside the list are ignored.
+        app.handle_mouse(mouse(click, rows.x, app.list_area.y));
+        app.handle_mouse(mouse(click, app.list_area.right() + 1, rows.y));
+        assert_eq!(app.list_state.selected(), Some(3));
+
+        app.handle_mouse(mouse(MouseEventKind::ScrollDown, 0, 0));
+        assert_eq!(app.list_state.selected(), Some(4));
+        for _ in 0..10 {
+            app.handle_mouse(mouse(MouseEventKind::ScrollUp, 0, 0));
+        }
+        assert_eq!(app.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn counted_delete_stops_at_the_end_of_the_list() {
+        let mut app = app();
+        let len = app.view.len();
+        let txids = app.txids();
+
+        app.select_index(1);
+        app.delete_selected(3);
+        assert_eq!(app.view.len(), len - 3);
+        assert!(app.mempool.contains_txid(&txids[0]));
+        assert!(!app.mempool.contains_txid(&txids[3]));
+        assert_eq!(app.selected_txid(), Some(txids[4]));
+
+        app.last();
+        app.delete_selected(10);
+        assert_eq!(app.view.len(), len - 4);
+        assert_eq!(app.list_state.selected(), Some(len - 5));
+    }
+
+    #[test]
+    fn commands_jump_by_number_and_txid() {
+        let mut app = app();
+        let txids = app.txids();
+
+        app.run_command(" 5 ").unwrap();
+        assert_eq!(app.selected_index(), Some(4));
+
+        let prefix = &txids[7].to_string()[..10];
+        app.run_command(&format!("txid {}", prefix.to_uppercase()))
+            .unwrap();
+        assert_eq!(app.selected_index(), Some(7));
+
+        assert!(app.run_command("0").is_err());
+        assert!(app.run_command(&(txids.len() + 1).to_string()).is_err());
+        assert!(app.run_command("txid").is_err());
+        assert!(app.run_command("frobnicate").is_err());
+        for command in [
+            format!("txid{prefix}"),
+            "address bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq".to_string(),
+            "versions 1".to_string(),
+        ] {
+            assert_eq!(
+                app.run_command(&command),
+                Err(format!("Unknown command: {command}"))
+            );
+        }
+        assert_eq!(app.selected_index(), Some(7));
+        assert!(app.address_filter.is_none());
+
+        app.filter = txids[2].to_string();
+        app.apply_filter();
+        assert!(app.run_command("8").is_err());
+    }
+
+    #[test]
+    fn edits_mark_the_dump_dirty_until_saved() {
+        let dir = std::env::temp_dir().join(format!("windfish-dirty-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut app = app();
+        app.output_path = dir.join("out.dat");
+        assert!(!app.dirty);
+        app.cycle_sort();
+        app.run_command("3").unwrap();
+        assert!(!app.dirty);
+
+        app.edit_time("1700000000").unwrap();
+        assert!(app.dirty);
+        app.save().unwrap();
+        assert!(!app.dirty);
+
+        app.run_command("version 2").unwrap();
+        assert!(app.dirty);
+        app.output_path = dir.join("missing/out.dat");
+        assert!(app.save().is_err());
+        assert!(app.dirty);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn help_lists_every_group_and_scrolls() {
+        let mut app = app();
+        app.mode = Mode::Help;
+        let shown = screen(&mut app, 120, 200);
+        for (group, _) in KEYBINDINGS {
+            assert!(shown.contains(group), "{group}");
+        }
+        assert!(shown.contains("Clear all fee deltas"));
+
+        app.help_scroll = u16::MAX;
+        screen(&mut app, 120, 200);
+        let lines: usize = KEYBINDINGS.iter().map(|(_, b)| b.len() + 2).sum::<usize>() - 1;
+        assert_eq!(usize::from(app.help_scroll), lines - 1);
+    }
+
+    #[test]
+    fn tab_moves_focus_and_the_highlighted_border() {
+        let mut app = app();
+        let mut terminal = Terminal::new(TestBackend::new(120, 12)).unwrap();
+        let mut list_border = |app: &mut App| {
+            terminal.draw(|f| ui(f, app)).unwrap();
+            let corner = &terminal.backend().buffer()[app.list_area.as_position()];
+            (corner.fg, corner.symbol().to_string())
+        };
+        assert_eq!(list_border(&mut app), (app.theme.accent, "┏".to_string()));
+
+        app.toggle_focus();
+        assert_eq!(app.focus, Focus::Details);
+        assert_eq!(
+            list_border(&mut app),
+            (app.theme.panel_border, "┌".to_string())
+        );
+
+        // The mono theme colors both borders alike, so only the line shows focus.
+        app.theme = ThemeName::Mono.theme();
+        assert_eq!(list_border(&mut app).1, "┌");
+        app.toggle_focus();
+        assert_eq!(list_border(&mut app).1, "┏");
+        app.toggle_focus();
+
+        let (column, row) = (app.list_area.x + 2, app.list_area.y + 1);
+        let mouse = |kind| MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.handle_mouse(mouse(MouseEventKind::ScrollDown));
+        assert_eq!(app.selected_index(), Some(0));
+        assert_eq!(app.details_scroll(), 1);
+
+        app.handle_mouse(mouse(MouseEventKind::Down(MouseButton::Left)));
+        assert_eq!(app.focus, Focus::List);
+        app.handle_mouse(mouse(MouseEventKind::ScrollDown));
+        assert_eq!(app.selected_index(), Some(1));
+    }
+
+    #[test]
+    fn details_scroll_stops_at_the_ends_and_resets_on_selection() {
+        let mut app = app();
+        let mut terminal = Terminal::new(TestBackend::new(120, 12)).unwrap();
+
+        app.scroll_details(-1);
+        assert_eq!(app.details_scroll(), 0);
+        app.scroll_details(3);
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        assert_eq!(app.details_scroll(), 3);
+
+        app.scroll_details(i16::MAX);
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        let scroll = app.details_scroll();
+        assert!(scroll > 3 && scroll < 100);
+
+        app.next();
+        assert_eq!(app.details_scroll(), 0);
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        app.previous();
+        assert_eq!(app.details_scroll(), 0);
+    }
+
+    #[test]
+    fn version_command_converts_the_dump() {
+        let mut app = app();
+        assert_eq!(app.mempool.version, 1);
+
+        app.run_command("version 2").unwrap();
+        let key = *app.mempool.xor_key().unwrap();
+        app.run_command("version 2").unwrap();
+        assert_eq!(app.mempool.xor_key(), Some(&key));
+
+        app.run_command("version 2 0102030405060708").unwrap();
+        assert_eq!(app.mempool.xor_key(), Some(&[1, 2, 3, 4, 5, 6, 7, 8]));
+
+        for bad in ["version", "version 3", "version 2 0102", "version 1 00"] {
+            assert!(app.run_command(bad).is_err(), "{bad}");
+        }
+        assert_eq!(app.mempool.version, 2);
+
+        app.run_command("version 1").unwrap();
+        assert_eq!(app.mempool.version, 1);
+        assert_eq!(app.mempool.xor_key(), None);
+    }
+
+    #[test]
+    fn addr_command_lists_transactions_paying_an_address() {
+        let mut app = app();
+        let (i, address) = app
+            .mempool
+            .txs
+            .iter()
+            .enumerate()
+            .find_map(|(i, txn)| {
+                let address =
+                    Address::from_script(&txn.tx.output[0].script_pubkey, Network::Bitcoin).ok()?;
+                Some((i, address))
+            })
+            .unwrap();
+
+        app.run_command(&format!("addr {address}")).unwrap();
+        assert!(app.view.contains(&i));
+        assert!(app.view.len() < app.mempool.txs.len());
+        assert!(list_title(&app).contains(&format!("addr {address}")));
+        assert!(app.run_command("addr notanaddress").is_err());
+        assert!(app.view.contains(&i));
+
+        app.run_command("addr").unwrap();
+        assert_eq!(app.view.len(), app.mempool.txs.len());
+    }
+
+    #[test]
+    fn export_writes_hex_or_raw_bytes() {
+        let mut app = app();
+        app.select_index(2);
+        let tx = app.mempool.txs[2].tx.clone();
+        let dir = std::env::temp_dir().join(format!("windfish-export-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let hex_path = dir.join("tx.hex");
+        app.export_tx(hex_path.to_str().unwrap()).unwrap();
+        let hex = std::fs::read_to_string(&hex_path).unwrap();
+        assert_eq!(decode_tx_hex(&hex).unwrap(), tx);
+
+        let bin_path = dir.join("tx.bin");
+        app.export_tx(bin_path.to_str().unwrap()).unwrap();
+        let bytes = std::fs::read(&bin_path).unwrap();
+        assert_eq!(
+            Transaction::consensus_decode(&mut bytes.as_slice()).unwrap(),
+            tx
+        );
+
+        assert!(
+            app.export_tx(dir.join("missing/tx.hex").to_str().unwrap())
+                .is_err()
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stdio_paths_require_a_command_line_operation() {
+        let check = |args: &[&str]| {
+            Args::try_parse_from(std::iter::once(&"windfish-tui").chain(args))
+                .unwrap()
+                .check_stdio()
+        };
+        assert!(check(&["-i", "-", "--summary"]).is_ok());
+        assert!(check(&["-i", "-", "-o", "-", "--delete", &"0".repeat(64)]).is_ok());
+        assert!(check(&["-i", "-", "-o", "out.dat"]).is_err());
+        assert!(check(&["-i", "in.dat", "-o", "-"]).is_err());
+        assert!(check(&["-i", "-", "--in-place", "--dry-run"]).is_err());
+        assert!(check(&["-i", "in.dat", "-o", "-", "--summary"]).is_err());
+    }
+
+    #[test]
+    fn csv_export_does_not_need_an_output() {
+        let parse =
+            |args: &[&str]| Args::try_parse_from(std::iter::once(&"windfish-tui").chain(args));
+        let args = parse(&["-i", "in.dat", "--export-csv", "out.csv"]).unwrap();
+        assert!(args.is_non_interactive());
+        assert!(args.output_path().is_none());
+        assert!(parse(&["-i", "in.dat"]).is_err());
+    }
+
+    #[test]
+    fn json_summary_matches_the_dump() {
+        let mempool = fixture();
+        let json = Summary::compute(&mempool).to_json();
+        assert_eq!(json["version"], mempool.version);
+        assert_eq!(json["count"], mempool.txs.len());
+        assert_eq!(json["total_vsize"], mempool.total_vsize());
+        assert_eq!(
+            json["total_output_value"],
+            mempool.total_output_value().unwrap().to_sat()
+        );
+        assert_eq!(json["deltas_count"], mempool.map_deltas.len());
+        assert_eq!(json["oldest_time"], mempool.oldest().unwrap().time);
+        assert_eq!(json["newest_time"], mempool.newest().unwrap().time);
+
+        let empty = Summary::compute(&MempoolSerde::empty(mempool.version)).to_json();
+        assert_eq!(empty["count"], 0);
+        assert_eq!(empty["fee_delta"]["total"], 0);
+        assert!(empty["fee_delta"]["min"].is_null());
+        assert!(empty["oldest_time"].is_null());
+    }
+
+    #[test]
+    fn duplicates_are_flagged_in_the_header_and_details() {
+        let mut mempool = fixture();
+        mempool.txs.push(mempool.txs[2].clone());
+        let copy = mempool.txs.len();
+        let mut app = app_for(mempool);
+        app.select_index(2);
+        let shown = screen(&mut app, 200, 40);
+        assert!(shown.contains("1 duplicated"));
+        assert!(shown.contains(&format!("Duplicate: also at {copy}")));
+
+        app.select_index(3);
+        assert!(!screen(&mut app, 200, 40).contains("Duplicate:"));
+    }
+
+    #[test]
+    fn details_mark_inputs_spending_the_dump() {
+        let mut mempool = fixture();
+        let parent = mempool.txs[0].txid();
+        mempool.txs[1].tx_mut().input[0].previous_output = bitcoin::OutPoint::new(parent, 3);
+        let mut app = app_for(mempool);
+        app.select_index(1);
+        let shown = screen(&mut app, 160, 60);
+        assert!(shown.contains(&format!("{}:3", short_txid(&parent))));
+        assert!(shown.contains("(in dump)"));
+
+        app.select_index(0);
+        assert!(!screen(&mut app, 160, 60).contains("(in dump)"));
+    }
+
+    #[test]
+    fn version_convert_checks_the_key() {
+        assert_eq!(
+            parse_xor_key("0001020304050607"),
+            Ok([0, 1, 2, 3, 4, 5, 6, 7])
+        );
+        assert!(parse_xor_key("00010203").is_err());
+        assert!(parse_xor_key("000102030405060708").is_err());
+        assert!(parse_xor_key("not hex!").is_err());
+
+        let parse = |version, key: Option<&str>| {
+            let mut argv = vec![
+                "windfish-tui",
+                "-i",
+                "in.dat",
+                "-o",
+                "out.dat",
+                "--version-convert",
+                version,
+            ];
+            argv.extend(key.into_iter().flat_map(|key| ["--xor-key", key]));
+            Args::try_parse_from(argv).unwrap()
+        };
+        let mut mempool = fixture();
+
+        let upgrade = parse("v2", Some("0001020304050607"));
+        assert!(upgrade.is_non_interactive());
+        convert_version(&mut mempool, &upgrade).unwrap();
+        assert_eq!(mempool.version, windfish::MEMPOOL_DUMP_VERSION);
+        assert_eq!(mempool.xor_key, Some([0, 1, 2, 3, 4, 5, 6, 7]));
+
+        convert_version(&mut mempool, &parse("v1", None)).unwrap();
+        assert_eq!(mempool.version, windfish::MEMPOOL_DUMP_VERSION_NO_XOR_KEY);
+        assert_eq!(mempool.xor_key, None);
+        assert!(convert_version(&mut mempool, &parse("v1", Some("0001020304050607"))).is_err());
+
+        // Without --xor-key a random key is generated.
+        convert_version(&mut mempool, &parse("v2", None)).unwrap();
+        assert!(mempool.xor_key.is_some());
+    }
+
+    #[test]
+    fn tick_ms_is_range_checked() {
+        let parse = |ms: &str| {
+            Args::try_parse_from(["windfish-tui", "-i", "in.dat", "--summary", "--tick-ms", ms])
+                .map(|args| args.tick_ms)
+        };
+        assert_eq!(parse("10").unwrap(), Some(10));
+        assert_eq!(parse("1000").unwrap(), Some(1000));
+        assert!(parse("9").is_err());
+        assert!(parse("1001").is_err());
+    }
+
+    #[test]
+    fn restore_reselects_the_saved_transaction() {
+        let mut app = app();
+        app.cycle_sort();
+        app.select_index(3);
+        let session = app.session();
+        assert_eq!(session.txid, Some(app.mempool.txs[3].txid()));
+
+        let mut reopened = app_for(fixture());
+        reopened.restore(session);
+        assert_eq!(reopened.sort_mode, app.sort_mode);
+        assert_eq!(reopened.selected_index(), Some(3));
+
+        // A transaction deleted since falls back to the top of the list.
+        let mut edited = fixture();
+        edited.txs.remove(3);
+        let mut reopened = app_for(edited);
+        reopened.restore(session);
+        assert_eq!(reopened.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn abbreviate_handles_short_and_wide_ids() {
+        assert_eq!(abbreviate(""), "");
+        assert_eq!(abbreviate("abc"), "abc");
+        assert_eq!(abbreviate(&"a".repeat(19)), "a".repeat(19));
+        assert_eq!(abbreviate("0123456789abcdefghij"), "01234567...cdefghij");
+        assert_eq!(
+            abbreviate(&"é".repeat(30)),
+            format!("{0}...{0}", "é".repeat(8))
+        );
+    }
+}
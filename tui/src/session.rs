@@ -0,0 +1,104 @@
+//! Selection and sort order remembered between runs on the same input.
+
+use crate::SortMode;
+use bitcoin::{
+    Txid,
+    hashes::{Hash, sha256},
+};
+use serde_json::{Value, json};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// What is restored when the same input is opened again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Session {
+    pub sort_mode: SortMode,
+    /// The selected transaction, kept by TXID so it is found again even if
+    /// the dump was edited in between
+    pub txid: Option<Txid>,
+}
+
+impl Session {
+    /// Where the session for `input` is kept: a file under the user's config
+    /// directory named by a hash of the input's absolute path.
+    pub fn path_for(input: &Path) -> Option<PathBuf> {
+        let name = file_name(input).ok()?;
+        Some(
+            dirs::config_dir()?
+                .join("windfish")
+                .join("sessions")
+                .join(name),
+        )
+    }
+
+    /// Reads a session saved at `path`. A missing or unreadable file means
+    /// there is nothing to restore.
+    pub fn load(path: &Path) -> Option<Self> {
+        let value: Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+        Some(Self {
+            sort_mode: SortMode::from_name(value["sort"].as_str()?)?,
+            txid: value["txid"]
+                .as_str()
+                .and_then(|txid| Txid::from_str(txid).ok()),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let value = json!({
+            "sort": self.sort_mode.name(),
+            "txid": self.txid.map(|txid| txid.to_string()),
+        });
+        std::fs::write(path, format!("{value:#}\n"))
+    }
+}
+
+/// Names the session file after the input's absolute path, so the same dump
+/// opened from another directory shares its session.
+fn file_name(input: &Path) -> io::Result<String> {
+    let input = std::fs::canonicalize(input)?;
+    let hash = sha256::Hash::hash(input.as_os_str().as_encoded_bytes());
+    Ok(format!("{}.json", &hash.to_string()[..16]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_roundtrip_and_are_keyed_by_path() {
+        let dir = std::env::temp_dir().join(format!("windfish-session-{}", std::process::id()));
+        let path = dir.join("sessions").join("test.json");
+        let session = Session {
+            sort_mode: SortMode::Time,
+            txid: Some(Txid::from_byte_array([7; 32])),
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path), Some(session));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(Session::load(&path), None);
+
+        let v1 = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test/mempool_t4_v1_001.dat"
+        ));
+        let v2 = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test/mempool_t4_v2_001.dat"
+        ));
+        let relative = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../test/../test/mempool_t4_v1_001.dat"
+        ));
+        assert_eq!(file_name(v1).unwrap(), file_name(relative).unwrap());
+        assert_ne!(file_name(v1).unwrap(), file_name(v2).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
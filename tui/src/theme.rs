@@ -0,0 +1,217 @@
+//! Color themes for the TUI.
+
+use clap::ValueEnum;
+use ratatui::style::Color;
+
+/// Theme selectable with `--theme`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ThemeName {
+    /// Green on black, with an animated background
+    #[default]
+    Matrix,
+    /// The terminal's own colors, readable on light and dark backgrounds
+    Mono,
+    /// Bright text on black
+    HighContrast,
+}
+
+impl ThemeName {
+    pub const fn theme(self) -> &'static Theme {
+        match self {
+            Self::Matrix => &MATRIX,
+            Self::Mono => &MONO,
+            Self::HighContrast => &HIGH_CONTRAST,
+        }
+    }
+}
+
+/// Colors used by `ui`, by role.
+pub struct Theme {
+    /// Whether the background pulses and the title glows, unless
+    /// `--no-animation` is given
+    pub animated: bool,
+    pub background: Color,
+    pub header_bg: Color,
+    pub header_border: Color,
+    pub panel_bg: Color,
+    pub panel_border: Color,
+    pub footer_bg: Color,
+    pub footer_border: Color,
+    /// Title, panel titles and the TXID in the details panel
+    pub title: Color,
+    /// Field labels, header counts and key help
+    pub label: Color,
+    pub text: Color,
+    /// List indices and hints
+    pub muted: Color,
+    pub item: Color,
+    pub item_selected: Color,
+    pub selected_bg: Color,
+    /// Unbroadcast marker and the titles of the hex and unbroadcast popups
+    pub accent: Color,
+    pub number: Color,
+    pub time: Color,
+    pub fee: Color,
+    pub amount: Color,
+    /// Separator lines in the details panel
+    pub rule: Color,
+    /// Status messages and the input popup title
+    pub status: Color,
+    pub popup_border: Color,
+    pub popup_bg: Color,
+    pub hex: Color,
+    pub hex_border: Color,
+    pub hex_bg: Color,
+    pub hex_selected_bg: Color,
+    /// Input errors and the delete confirmation
+    pub danger: Color,
+    pub danger_border: Color,
+    pub danger_bg: Color,
+    /// Text on the mode and network badges
+    pub badge_fg: Color,
+    /// Text on the insert and test network badges
+    pub badge_dark_fg: Color,
+    pub normal_badge: Color,
+    pub insert_badge: Color,
+    pub filter_badge: Color,
+    /// Badge of the raw hex and unbroadcast popups
+    pub view_badge: Color,
+    pub delete_badge: Color,
+    pub edit_badge: Color,
+    pub mainnet_badge: Color,
+    pub network_badge: Color,
+}
+
+const MATRIX: Theme = Theme {
+    animated: true,
+    background: Color::Rgb(0, 10, 0),
+    header_bg: Color::Rgb(0, 20, 0),
+    header_border: Color::Rgb(0, 180, 0),
+    panel_bg: Color::Rgb(0, 15, 0),
+    panel_border: Color::Rgb(0, 120, 0),
+    footer_bg: Color::Rgb(0, 10, 0),
+    footer_border: Color::Rgb(0, 80, 0),
+    title: Color::Rgb(0, 255, 100),
+    label: Color::Rgb(0, 150, 0),
+    text: Color::White,
+    muted: Color::DarkGray,
+    item: Color::Rgb(0, 200, 0),
+    item_selected: Color::Rgb(0, 255, 0),
+    selected_bg: Color::Rgb(0, 50, 0),
+    accent: Color::Rgb(0, 200, 255),
+    number: Color::Cyan,
+    time: Color::Yellow,
+    fee: Color::Magenta,
+    amount: Color::Rgb(255, 200, 0),
+    rule: Color::Rgb(0, 100, 0),
+    status: Color::Rgb(255, 255, 0),
+    popup_border: Color::Rgb(200, 200, 0),
+    popup_bg: Color::Rgb(20, 20, 0),
+    hex: Color::Rgb(0, 220, 255),
+    hex_border: Color::Rgb(0, 120, 200),
+    hex_bg: Color::Rgb(0, 10, 25),
+    hex_selected_bg: Color::Rgb(0, 40, 70),
+    danger: Color::Rgb(255, 80, 80),
+    danger_border: Color::Rgb(200, 0, 0),
+    danger_bg: Color::Rgb(30, 0, 0),
+    badge_fg: Color::White,
+    badge_dark_fg: Color::Black,
+    normal_badge: Color::Rgb(0, 100, 0),
+    insert_badge: Color::Rgb(100, 100, 0),
+    filter_badge: Color::Rgb(0, 100, 100),
+    view_badge: Color::Rgb(0, 60, 120),
+    delete_badge: Color::Rgb(150, 0, 0),
+    edit_badge: Color::Rgb(100, 0, 100),
+    mainnet_badge: Color::Rgb(180, 0, 0),
+    network_badge: Color::Rgb(180, 140, 0),
+};
+
+// `Reset` keeps the terminal's own colors; highlights use gray, which reads
+// on both light and dark backgrounds.
+const MONO: Theme = Theme {
+    animated: false,
+    background: Color::Reset,
+    header_bg: Color::Reset,
+    header_border: Color::Reset,
+    panel_bg: Color::Reset,
+    panel_border: Color::Reset,
+    footer_bg: Color::Reset,
+    footer_border: Color::Reset,
+    title: Color::Reset,
+    label: Color::Reset,
+    text: Color::Reset,
+    muted: Color::Reset,
+    item: Color::Reset,
+    item_selected: Color::Black,
+    selected_bg: Color::Gray,
+    accent: Color::Reset,
+    number: Color::Reset,
+    time: Color::Reset,
+    fee: Color::Reset,
+    amount: Color::Reset,
+    rule: Color::Reset,
+    status: Color::Reset,
+    popup_border: Color::Reset,
+    popup_bg: Color::Reset,
+    hex: Color::Reset,
+    hex_border: Color::Reset,
+    hex_bg: Color::Reset,
+    hex_selected_bg: Color::Gray,
+    danger: Color::Reset,
+    danger_border: Color::Reset,
+    danger_bg: Color::Reset,
+    badge_fg: Color::Black,
+    badge_dark_fg: Color::Black,
+    normal_badge: Color::Gray,
+    insert_badge: Color::Gray,
+    filter_badge: Color::Gray,
+    view_badge: Color::Gray,
+    delete_badge: Color::Gray,
+    edit_badge: Color::Gray,
+    mainnet_badge: Color::Gray,
+    network_badge: Color::Gray,
+};
+
+const HIGH_CONTRAST: Theme = Theme {
+    animated: false,
+    background: Color::Black,
+    header_bg: Color::Black,
+    header_border: Color::White,
+    panel_bg: Color::Black,
+    panel_border: Color::White,
+    footer_bg: Color::Black,
+    footer_border: Color::White,
+    title: Color::Yellow,
+    label: Color::White,
+    text: Color::White,
+    muted: Color::Gray,
+    item: Color::White,
+    item_selected: Color::Black,
+    selected_bg: Color::Yellow,
+    accent: Color::LightCyan,
+    number: Color::LightCyan,
+    time: Color::Yellow,
+    fee: Color::LightMagenta,
+    amount: Color::Yellow,
+    rule: Color::White,
+    status: Color::Yellow,
+    popup_border: Color::Yellow,
+    popup_bg: Color::Black,
+    hex: Color::White,
+    hex_border: Color::LightCyan,
+    hex_bg: Color::Black,
+    hex_selected_bg: Color::Blue,
+    danger: Color::LightRed,
+    danger_border: Color::LightRed,
+    danger_bg: Color::Black,
+    badge_fg: Color::Black,
+    badge_dark_fg: Color::Black,
+    normal_badge: Color::LightGreen,
+    insert_badge: Color::Yellow,
+    filter_badge: Color::LightCyan,
+    view_badge: Color::LightBlue,
+    delete_badge: Color::LightRed,
+    edit_badge: Color::LightMagenta,
+    mainnet_badge: Color::LightRed,
+    network_badge: Color::Yellow,
+};